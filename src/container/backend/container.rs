@@ -0,0 +1,141 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bollard::Docker;
+use bollard::query_parameters::KillContainerOptions;
+use futures_util::future::BoxFuture;
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+
+use super::super::CollectorTestHarnessBuilder;
+use super::super::cleanup;
+use super::super::logs::LogStream;
+use super::super::network::NetworkMode;
+use super::{CollectorBackend, LaunchSpec};
+use crate::error::Result;
+
+/// A collector in a Docker container.
+pub(crate) struct ContainerBackend {
+    container: ContainerAsync<GenericImage>,
+    container_id: String,
+    docker: Docker,
+    network: NetworkMode,
+    log_stream: LogStream,
+}
+
+impl ContainerBackend {
+    pub(crate) async fn launch(
+        builder: &CollectorTestHarnessBuilder,
+        image: GenericImage,
+        spec: LaunchSpec<'_>,
+    ) -> Result<Self> {
+        let image = builder.network.expose(image, &spec.exposed_ports);
+        let mut request = spec
+            .rendered
+            .copy_into(image.with_labels(builder.labels()))
+            .with_cmd(spec.args);
+
+        if let Some(storage) = spec.storage {
+            request = request.with_mount(storage.mount());
+        }
+        request = builder.network.apply(request, &spec.exposed_ports);
+        for (key, value) in &spec.env {
+            request = request.with_env_var(key, value);
+        }
+
+        let docker = Docker::connect_with_local_defaults()?;
+        let container = request.start().await?;
+        let container_id = container.id().to_string();
+        let log_stream = spec
+            .logs
+            .attach(&docker, &container_id, spec.dump_logs_on_drop);
+
+        Ok(Self {
+            container,
+            container_id,
+            docker,
+            network: builder.network,
+            log_stream,
+        })
+    }
+}
+
+impl CollectorBackend for ContainerBackend {
+    fn id(&self) -> &str {
+        &self.container_id
+    }
+
+    fn pid(&self) -> Option<u32> {
+        None
+    }
+
+    fn host_port(&self, port: u16) -> BoxFuture<'_, Result<u16>> {
+        Box::pin(self.network.host_port(&self.container, port))
+    }
+
+    fn is_running(&self) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(async move { Ok(self.container.is_running().await?) })
+    }
+
+    fn exit_code(&self) -> BoxFuture<'_, Result<Option<i64>>> {
+        Box::pin(async move { Ok(self.container.exit_code().await?) })
+    }
+
+    fn stop(&self, timeout: Duration) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let seconds = i32::try_from(timeout.as_secs()).unwrap_or(i32::MAX);
+            self.container.stop_with_timeout(Some(seconds)).await?;
+            Ok(())
+        })
+    }
+
+    fn signal<'a>(&'a self, signal: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let options = KillContainerOptions {
+                signal: signal.to_string(),
+            };
+            self.docker
+                .kill_container(&self.container_id, Some(options))
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn pause(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { Ok(self.container.pause().await?) })
+    }
+
+    fn unpause(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { Ok(self.container.unpause().await?) })
+    }
+
+    fn start(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            // Log lines carry one-second resolution for `since`, so start in
+            // a fresh second to avoid re-reading lines from the previous run.
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            let since = now.as_secs() + 1;
+            tokio::time::sleep(Duration::from_secs(since).saturating_sub(now)).await;
+
+            self.container.start().await?;
+            self.log_stream.reattach(
+                &self.docker,
+                &self.container_id,
+                i32::try_from(since).unwrap_or(i32::MAX),
+            );
+            Ok(())
+        })
+    }
+}
+
+impl Drop for ContainerBackend {
+    /// Removes the container straight away when a test panics, rather than
+    /// relying on drop order, so that a host-network collector does not keep
+    /// its ports after the test has failed.
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            cleanup::remove_container_blocking(&self.container_id);
+        }
+    }
+}
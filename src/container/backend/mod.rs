@@ -0,0 +1,100 @@
+mod container;
+mod process;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+
+use super::config::RenderedConfigs;
+use super::logs::CollectorLogs;
+use super::storage::Storage;
+use crate::error::Result;
+
+pub(crate) use container::ContainerBackend;
+pub(crate) use process::{ProcessBackend, WorkDir};
+
+/// Selects the local binary backend for every builder that does not choose
+/// one explicitly, so a suite can run on machines without Docker.
+pub const BINARY_ENV_VAR: &str = "COLLECTOR_TESTER_BINARY";
+
+/// Where the harness runs the collector.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BackendKind {
+    /// A Docker container started through testcontainers.
+    #[default]
+    Container,
+    /// An `otelcol` or `otelcol-contrib` binary run as a child process. The
+    /// collector shares the host network, configs are written to a temp
+    /// directory, and files copied under the distribution's config directory
+    /// are placed there instead.
+    LocalProcess { binary: PathBuf },
+}
+
+impl BackendKind {
+    pub fn local_process(binary: impl Into<PathBuf>) -> Self {
+        BackendKind::LocalProcess {
+            binary: binary.into(),
+        }
+    }
+
+    /// [`BackendKind::LocalProcess`] if [`BINARY_ENV_VAR`] is set, otherwise
+    /// [`BackendKind::Container`].
+    pub fn from_env() -> Self {
+        match std::env::var_os(BINARY_ENV_VAR) {
+            Some(binary) if !binary.is_empty() => BackendKind::local_process(binary),
+            _ => BackendKind::Container,
+        }
+    }
+}
+
+/// A started collector, as driven by
+/// [`CollectorTestHarness`](super::CollectorTestHarness).
+///
+/// Methods return boxed futures so the harness can hold any backend behind
+/// `dyn CollectorBackend`.
+pub trait CollectorBackend: Send + Sync {
+    /// The container ID, or a generated name for a local process. Stable
+    /// across restarts.
+    fn id(&self) -> &str;
+
+    /// The collector's process ID on this host, when it runs as a local
+    /// process.
+    fn pid(&self) -> Option<u32>;
+
+    /// The host port that reaches `port` inside the collector's network.
+    fn host_port(&self, port: u16) -> BoxFuture<'_, Result<u16>>;
+
+    fn is_running(&self) -> BoxFuture<'_, Result<bool>>;
+
+    /// The exit code of the last run, or `None` while the collector is
+    /// running. A collector killed by a signal reports `128 + signal`.
+    fn exit_code(&self) -> BoxFuture<'_, Result<Option<i64>>>;
+
+    /// Sends SIGTERM and waits up to `timeout` before sending SIGKILL.
+    fn stop(&self, timeout: Duration) -> BoxFuture<'_, Result<()>>;
+
+    /// Sends a signal such as `"SIGTERM"` or `"SIGHUP"`.
+    fn signal<'a>(&'a self, signal: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    fn pause(&self) -> BoxFuture<'_, Result<()>>;
+
+    fn unpause(&self) -> BoxFuture<'_, Result<()>>;
+
+    /// Runs the exited collector again with the same config and
+    /// environment, appending its output to the same logs.
+    fn start(&mut self) -> BoxFuture<'_, Result<()>>;
+}
+
+/// Everything a backend needs to start one collector, already rendered for
+/// that backend.
+pub(crate) struct LaunchSpec<'a> {
+    pub(crate) rendered: &'a RenderedConfigs,
+    pub(crate) args: Vec<String>,
+    /// In order; later values for the same variable win.
+    pub(crate) env: Vec<(String, String)>,
+    pub(crate) exposed_ports: Vec<u16>,
+    pub(crate) storage: Option<&'a Storage>,
+    pub(crate) logs: CollectorLogs,
+    pub(crate) dump_logs_on_drop: bool,
+}
@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use testcontainers::CopyDataSource;
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+
+use super::super::config::RenderedConfigs;
+use super::super::logs::LogStream;
+use super::super::{unique_suffix, validate};
+use super::{CollectorBackend, LaunchSpec};
+use crate::error::{Error, Result};
+
+/// A temp directory holding a local collector's configs and copied files,
+/// removed when the last backend using it is dropped.
+#[derive(Debug)]
+pub(crate) struct WorkDir {
+    path: PathBuf,
+    /// The distribution's config directory, which this directory stands in
+    /// for.
+    config_dir: PathBuf,
+}
+
+impl WorkDir {
+    pub(crate) fn create(config_dir: &str) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("collector-tester-{}", unique_suffix()));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self {
+            path,
+            config_dir: PathBuf::from(config_dir),
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes the rendered configs and copied files. Files aimed at the
+    /// distribution's config directory are placed in this directory instead.
+    fn write(&self, rendered: &RenderedConfigs) -> Result<()> {
+        for (target, source) in rendered.copies() {
+            let target = Path::new(target);
+            let path = if target.starts_with(&self.path) {
+                target.to_path_buf()
+            } else if let Ok(relative) = target.strip_prefix(&self.config_dir) {
+                self.path.join(relative)
+            } else {
+                return Err(Error::UnsupportedByBackend {
+                    feature: format!("copying a file to {}", target.display()),
+                });
+            };
+
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            match source {
+                CopyDataSource::Data(data) => std::fs::write(&path, data)?,
+                CopyDataSource::File(file) => {
+                    std::fs::copy(file, &path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// A collector binary run as a child process on the host network.
+pub(crate) struct ProcessBackend {
+    id: String,
+    command: CommandSpec,
+    child: Mutex<Child>,
+    pid: AtomicU32,
+    log_stream: LogStream,
+    _workdir: Arc<WorkDir>,
+}
+
+#[derive(Debug, Clone)]
+struct CommandSpec {
+    binary: PathBuf,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    dir: PathBuf,
+}
+
+impl CommandSpec {
+    fn spawn(&self) -> Result<Child> {
+        Ok(Command::new(&self.binary)
+            .args(&self.args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .current_dir(&self.dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?)
+    }
+}
+
+impl ProcessBackend {
+    pub(crate) async fn launch(
+        binary: &Path,
+        workdir: Arc<WorkDir>,
+        spec: LaunchSpec<'_>,
+    ) -> Result<Self> {
+        workdir.write(spec.rendered)?;
+
+        let command = CommandSpec {
+            binary: binary.to_path_buf(),
+            args: spec.args,
+            env: spec.env,
+            dir: workdir.path().to_path_buf(),
+        };
+        let mut child = command.spawn()?;
+        let pid = child.id().ok_or(Error::ProcessNotRunning)?;
+        let (stdout, stderr) = pipes(&mut child);
+
+        Ok(Self {
+            id: format!("otelcol-{}", unique_suffix()),
+            command,
+            child: Mutex::new(child),
+            pid: AtomicU32::new(pid),
+            log_stream: spec.logs.capture(stdout, stderr, spec.dump_logs_on_drop),
+            _workdir: workdir,
+        })
+    }
+
+    /// Runs `binary validate` against the rendered configs.
+    pub(crate) async fn validate(
+        binary: &Path,
+        workdir: &WorkDir,
+        rendered: &RenderedConfigs,
+        env: Vec<(String, String)>,
+        timeout: Duration,
+    ) -> Result<()> {
+        workdir.write(rendered)?;
+
+        let mut args = vec!["validate".to_string()];
+        args.extend(rendered.args.iter().cloned());
+        let command = CommandSpec {
+            binary: binary.to_path_buf(),
+            args,
+            env,
+            dir: workdir.path().to_path_buf(),
+        };
+
+        let output = tokio::time::timeout(timeout, command.spawn()?.wait_with_output())
+            .await
+            .map_err(|_| Error::ValidationTimeout { timeout })??;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Err(validate::invalid_config(exit_code(output.status), text))
+    }
+
+    /// Signals `child`. The caller holds it locked, so it cannot be reaped
+    /// and its pid reused by another process in the meantime.
+    fn send_signal(child: &mut Child, signal: &str) -> Result<()> {
+        let number = signal_number(signal)?;
+        if child.try_wait()?.is_some() {
+            return Err(Error::ProcessNotRunning);
+        }
+        let pid = child.id().ok_or(Error::ProcessNotRunning)?;
+        let pid = libc::pid_t::try_from(pid).map_err(|_| Error::ProcessNotRunning)?;
+        // SAFETY: `kill` has no memory safety requirements, and the locked
+        // child is unreaped, so `pid` still names it.
+        if unsafe { libc::kill(pid, number) } == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error().into())
+        }
+    }
+}
+
+/// Maps a signal name such as `"SIGTERM"` or `"TERM"` to its number.
+fn signal_number(signal: &str) -> Result<libc::c_int> {
+    let number = match signal.trim_start_matches("SIG") {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "KILL" => libc::SIGKILL,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        "TERM" => libc::SIGTERM,
+        "CONT" => libc::SIGCONT,
+        "STOP" => libc::SIGSTOP,
+        _ => {
+            return Err(Error::UnknownSignal {
+                signal: signal.to_string(),
+            });
+        }
+    };
+    Ok(number)
+}
+
+fn pipes(child: &mut Child) -> (tokio::process::ChildStdout, tokio::process::ChildStderr) {
+    (
+        child.stdout.take().expect("stdout is piped"),
+        child.stderr.take().expect("stderr is piped"),
+    )
+}
+
+/// Matches Docker's convention of `128 + signal` for a killed process.
+fn exit_code(status: ExitStatus) -> Option<i64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return Some(128 + i64::from(signal));
+        }
+    }
+    status.code().map(i64::from)
+}
+
+impl CollectorBackend for ProcessBackend {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn pid(&self) -> Option<u32> {
+        Some(self.pid.load(Ordering::Relaxed))
+    }
+
+    fn host_port(&self, port: u16) -> BoxFuture<'_, Result<u16>> {
+        Box::pin(async move { Ok(port) })
+    }
+
+    fn is_running(&self) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(async move { Ok(self.child.lock().await.try_wait()?.is_none()) })
+    }
+
+    fn exit_code(&self) -> BoxFuture<'_, Result<Option<i64>>> {
+        Box::pin(async move { Ok(self.child.lock().await.try_wait()?.and_then(exit_code)) })
+    }
+
+    fn stop(&self, timeout: Duration) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let mut child = self.child.lock().await;
+            if child.try_wait()?.is_some() {
+                return Ok(());
+            }
+
+            Self::send_signal(&mut child, "SIGTERM")?;
+            if tokio::time::timeout(timeout, child.wait()).await.is_err() {
+                child.start_kill()?;
+                child.wait().await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn signal<'a>(&'a self, signal: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { Self::send_signal(&mut *self.child.lock().await, signal) })
+    }
+
+    fn pause(&self) -> BoxFuture<'_, Result<()>> {
+        self.signal("SIGSTOP")
+    }
+
+    fn unpause(&self) -> BoxFuture<'_, Result<()>> {
+        self.signal("SIGCONT")
+    }
+
+    fn start(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let mut child = self.command.spawn()?;
+            let pid = child.id().ok_or(Error::ProcessNotRunning)?;
+            self.pid.store(pid, Ordering::Relaxed);
+            let (stdout, stderr) = pipes(&mut child);
+            self.log_stream.recapture(stdout, stderr);
+            *self.child.get_mut() = child;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bollard::Docker;
+use bollard::query_parameters::{ListContainersOptionsBuilder, RemoveContainerOptionsBuilder};
+
+use crate::error::Result;
+
+/// Present on every container the harness starts.
+pub const LABEL_MANAGED: &str = "collector-tester.managed";
+/// The [`run_id`] of the test process that started the container.
+pub const LABEL_RUN_ID: &str = "collector-tester.run-id";
+/// The pid of the test process that started the container.
+pub const LABEL_PID: &str = "collector-tester.pid";
+/// The test that started the container, when known.
+pub const LABEL_TEST: &str = "collector-tester.test";
+/// The [`host_id`] of the test process that started the container.
+pub const LABEL_HOST: &str = "collector-tester.host";
+
+/// How old a container must be before [`reap_stale_containers`] removes it.
+pub const DEFAULT_REAP_MIN_AGE: Duration = Duration::from_secs(60);
+
+/// Identifies this test process in container labels.
+pub fn run_id() -> &'static str {
+    static RUN_ID: OnceLock<String> = OnceLock::new();
+    RUN_ID.get_or_init(|| {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        format!("{}-{started}", std::process::id())
+    })
+}
+
+/// Identifies where this process's pid is meaningful: the host name and,
+/// on Linux, the boot id and PID namespace. The Docker daemon may be shared
+/// by test processes on other hosts or in other containers, whose pids say
+/// nothing about processes here.
+pub fn host_id() -> &'static str {
+    static HOST_ID: OnceLock<String> = OnceLock::new();
+    HOST_ID.get_or_init(|| {
+        let hostname = read_trimmed("/proc/sys/kernel/hostname").or_else(|| {
+            let output = Command::new("hostname").output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        });
+        let boot_id = read_trimmed("/proc/sys/kernel/random/boot_id");
+        let pid_namespace = std::fs::read_link("/proc/self/ns/pid")
+            .ok()
+            .map(|link| link.to_string_lossy().into_owned());
+        [hostname, boot_id, pid_namespace]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("/")
+    })
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    Some(std::fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+/// The name of the current test under the default test runner, which runs
+/// each test on a thread named after it.
+pub(crate) fn current_test_name() -> Option<String> {
+    std::thread::current()
+        .name()
+        .filter(|name| *name != "main")
+        .map(str::to_string)
+}
+
+pub(crate) fn labels(test_name: Option<&str>) -> Vec<(String, String)> {
+    let mut labels = vec![
+        (LABEL_MANAGED.to_string(), "true".to_string()),
+        (LABEL_RUN_ID.to_string(), run_id().to_string()),
+        (LABEL_PID.to_string(), std::process::id().to_string()),
+        (LABEL_HOST.to_string(), host_id().to_string()),
+    ];
+    if let Some(test_name) = test_name {
+        labels.push((LABEL_TEST.to_string(), test_name.to_string()));
+    }
+    labels
+}
+
+/// Force-removes a container from synchronous code, such as `Drop` while a
+/// test is panicking. The removal runs on its own thread and runtime so that
+/// it completes even when the test's runtime is unwinding.
+pub(crate) fn remove_container_blocking(container_id: &str) {
+    let container_id = container_id.to_string();
+    let removal = std::thread::spawn(move || {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        runtime.block_on(async {
+            if let Ok(docker) = Docker::connect_with_local_defaults() {
+                let _ = remove_container(&docker, &container_id).await;
+            }
+        });
+    });
+    let _ = removal.join();
+}
+
+async fn remove_container(docker: &Docker, container_id: &str) -> Result<()> {
+    let options = RemoveContainerOptionsBuilder::new()
+        .force(true)
+        .v(true)
+        .build();
+    match docker.remove_container(container_id, Some(options)).await {
+        Err(bollard::errors::Error::DockerResponseServerError {
+            status_code: 404, ..
+        }) => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Removes containers left behind by test processes that have exited, e.g.
+/// after a CI job was killed mid-run. Containers belonging to running test
+/// processes, including this one, are left alone, as are containers started
+/// from another host or PID namespace, whose owner cannot be checked, and
+/// containers younger than [`DEFAULT_REAP_MIN_AGE`]. Returns the IDs of the
+/// removed containers.
+pub async fn reap_stale_containers() -> Result<Vec<String>> {
+    reap_stale_containers_older_than(DEFAULT_REAP_MIN_AGE).await
+}
+
+/// [`reap_stale_containers`] with a custom minimum container age.
+pub async fn reap_stale_containers_older_than(min_age: Duration) -> Result<Vec<String>> {
+    let docker = Docker::connect_with_local_defaults()?;
+    let filters = HashMap::from([("label", vec![format!("{LABEL_MANAGED}=true")])]);
+    let options = ListContainersOptionsBuilder::new()
+        .all(true)
+        .filters(&filters)
+        .build();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let mut removed = Vec::new();
+    for container in docker.list_containers(Some(options)).await? {
+        let (Some(id), Some(labels)) = (container.id, container.labels) else {
+            continue;
+        };
+        if labels.get(LABEL_RUN_ID).map(String::as_str) == Some(run_id())
+            || labels.get(LABEL_HOST).map(String::as_str) != Some(host_id())
+        {
+            continue;
+        }
+        let age = container
+            .created
+            .and_then(|created| u64::try_from(created).ok())
+            .and_then(|created| now.checked_sub(Duration::from_secs(created)));
+        if age.is_none_or(|age| age < min_age) {
+            continue;
+        }
+        let owner_alive = labels
+            .get(LABEL_PID)
+            .and_then(|pid| pid.parse().ok())
+            .is_some_and(process_alive);
+        if owner_alive {
+            continue;
+        }
+
+        remove_container(&docker, &id).await?;
+        removed.push(id);
+    }
+    Ok(removed)
+}
+
+fn process_alive(pid: u32) -> bool {
+    if cfg!(target_os = "linux") {
+        Path::new("/proc").join(pid.to_string()).exists()
+    } else {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+}
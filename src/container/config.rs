@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use testcontainers::{ContainerRequest, CopyDataSource, GenericImage, ImageExt};
+
+use crate::error::{Error, Result};
+
+/// A collector configuration, rendered with `{{ name }}` placeholders
+/// substituted before it is copied into the container.
+///
+/// Strings convert to [`CollectorConfig::File`] so existing call sites that
+/// pass a path keep working; use [`CollectorConfig::inline`] for YAML text.
+#[derive(Debug, Clone)]
+pub enum CollectorConfig {
+    File(PathBuf),
+    Inline(String),
+    Yaml(serde_yaml::Value),
+}
+
+impl CollectorConfig {
+    pub fn file(path: impl AsRef<Path>) -> Self {
+        CollectorConfig::File(path.as_ref().to_path_buf())
+    }
+
+    pub fn inline(yaml: impl Into<String>) -> Self {
+        CollectorConfig::Inline(yaml.into())
+    }
+
+    pub fn yaml(value: serde_yaml::Value) -> Self {
+        CollectorConfig::Yaml(value)
+    }
+
+    /// Produces the config text with every placeholder replaced. A
+    /// placeholder without a value is an error rather than being left in
+    /// place, since the collector would reject it with a less useful message.
+    pub fn render(&self, vars: &HashMap<String, String>) -> Result<String> {
+        render_template(&self.template()?, vars)
+    }
+
+    /// The config text before placeholders are substituted.
+    pub(crate) fn template(&self) -> Result<String> {
+        Ok(match self {
+            CollectorConfig::File(path) => std::fs::read_to_string(path)?,
+            CollectorConfig::Inline(yaml) => yaml.clone(),
+            CollectorConfig::Yaml(value) => serde_yaml::to_string(value)?,
+        })
+    }
+}
+
+/// One `--config` argument to the collector. Sources are merged in order,
+/// so later sources override earlier ones.
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+    /// Rendered and copied into the container.
+    Config(CollectorConfig),
+    /// Passed through unchanged, e.g. `env:VAR`, `yaml:key: value`,
+    /// `file:/path/in/container` or an `http:` URL.
+    Uri(String),
+}
+
+impl From<CollectorConfig> for ConfigSource {
+    fn from(config: CollectorConfig) -> Self {
+        ConfigSource::Config(config)
+    }
+}
+
+impl From<PathBuf> for CollectorConfig {
+    fn from(path: PathBuf) -> Self {
+        CollectorConfig::File(path)
+    }
+}
+
+impl From<&PathBuf> for CollectorConfig {
+    fn from(path: &PathBuf) -> Self {
+        CollectorConfig::file(path)
+    }
+}
+
+impl From<&str> for CollectorConfig {
+    fn from(path: &str) -> Self {
+        CollectorConfig::file(path)
+    }
+}
+
+impl From<&Path> for CollectorConfig {
+    fn from(path: &Path) -> Self {
+        CollectorConfig::file(path)
+    }
+}
+
+impl From<serde_yaml::Value> for CollectorConfig {
+    fn from(value: serde_yaml::Value) -> Self {
+        CollectorConfig::Yaml(value)
+    }
+}
+
+/// Config sources rendered for one collector, with the files to copy in and
+/// the matching `--config` arguments.
+pub(crate) struct RenderedConfigs {
+    copies: Vec<(String, CopyDataSource)>,
+    pub(crate) args: Vec<String>,
+}
+
+impl RenderedConfigs {
+    pub(crate) fn render(
+        sources: &[ConfigSource],
+        files: &[(String, CopyDataSource)],
+        vars: &HashMap<String, String>,
+        config_dir: &str,
+    ) -> Result<Self> {
+        let mut copies = Vec::with_capacity(sources.len() + files.len());
+        let mut args = Vec::with_capacity(sources.len());
+
+        for source in sources {
+            match source {
+                ConfigSource::Config(config) => {
+                    let path = match copies.len() {
+                        0 => format!("{config_dir}/config.yaml"),
+                        n => format!("{config_dir}/config-{n}.yaml"),
+                    };
+                    let content = config.render(vars)?;
+                    args.push(format!("--config={path}"));
+                    copies.push((path, CopyDataSource::Data(content.into_bytes())));
+                }
+                ConfigSource::Uri(uri) => args.push(format!("--config={uri}")),
+            }
+        }
+
+        copies.extend(files.iter().cloned());
+        Ok(Self { copies, args })
+    }
+
+    pub(crate) fn copies(&self) -> &[(String, CopyDataSource)] {
+        &self.copies
+    }
+
+    pub(crate) fn copy_into(
+        &self,
+        mut request: ContainerRequest<GenericImage>,
+    ) -> ContainerRequest<GenericImage> {
+        for (path, source) in &self.copies {
+            request = request.with_copy_to(path.as_str(), source.clone());
+        }
+        request
+    }
+}
+
+fn render_template(template: &str, vars: &HashMap<String, String>) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        let value = vars.get(name).ok_or_else(|| Error::ConfigTemplate {
+            placeholder: name.to_string(),
+        })?;
+
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &rest[start + 2 + len + 2..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
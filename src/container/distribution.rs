@@ -0,0 +1,254 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use testcontainers::core::BuildImageOptions;
+use testcontainers::runners::AsyncBuilder;
+use testcontainers::{GenericBuildableImage, GenericImage};
+
+use super::{COLLECTOR_IMAGE, CONTAINER_CONFIG_DIR};
+use crate::error::{Error, Result};
+
+/// Where configs go in a custom distribution unless set with
+/// [`Distribution::config_dir`].
+pub const DEFAULT_DISTRIBUTION_CONFIG_DIR: &str = "/etc/otelcol";
+pub const DEFAULT_BASE_IMAGE: &str = "alpine:latest";
+const BUILT_IMAGE_PREFIX: &str = "collector-tester";
+
+/// The collector distribution a harness runs: a published image, an image
+/// built from a local Dockerfile, or a locally built binary (e.g. from the
+/// OpenTelemetry Collector Builder) wrapped into an image.
+///
+/// Built images are tagged with a SHA-256 of their build inputs and only
+/// built when no image with that tag exists, so unchanged distributions are
+/// built once per Docker host, whichever toolchain built the tests.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Distribution {
+    source: Source,
+    pub(crate) config_dir: String,
+    /// An image or tag set on a built distribution, which has neither.
+    invalid_override: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Source {
+    Image {
+        image: String,
+        tag: String,
+    },
+    Build {
+        name: String,
+        context: PathBuf,
+        dockerfile: Option<PathBuf>,
+    },
+    Binary {
+        name: String,
+        binary: PathBuf,
+        base_image: String,
+    },
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Self::contrib("latest")
+    }
+}
+
+impl Distribution {
+    /// A published image whose entrypoint is the collector.
+    pub fn image(image: impl Into<String>, tag: impl Into<String>) -> Self {
+        Self {
+            source: Source::Image {
+                image: image.into(),
+                tag: tag.into(),
+            },
+            config_dir: CONTAINER_CONFIG_DIR.to_string(),
+            invalid_override: None,
+        }
+    }
+
+    /// A tag of the default contrib image.
+    pub fn contrib(tag: impl Into<String>) -> Self {
+        Self::image(COLLECTOR_IMAGE, tag)
+    }
+
+    /// An image built from the `Dockerfile` in `context`, whose entrypoint
+    /// must be the collector.
+    pub fn build(name: impl Into<String>, context: impl Into<PathBuf>) -> Self {
+        Self {
+            source: Source::Build {
+                name: name.into(),
+                context: context.into(),
+                dockerfile: None,
+            },
+            config_dir: DEFAULT_DISTRIBUTION_CONFIG_DIR.to_string(),
+            invalid_override: None,
+        }
+    }
+
+    /// An image that runs `binary` on [`DEFAULT_BASE_IMAGE`]. The binary
+    /// must be built for Linux on the Docker host's architecture.
+    pub fn binary(name: impl Into<String>, binary: impl Into<PathBuf>) -> Self {
+        Self {
+            source: Source::Binary {
+                name: name.into(),
+                binary: binary.into(),
+                base_image: DEFAULT_BASE_IMAGE.to_string(),
+            },
+            config_dir: DEFAULT_DISTRIBUTION_CONFIG_DIR.to_string(),
+            invalid_override: None,
+        }
+    }
+
+    /// Builds with a Dockerfile other than `Dockerfile` in the context.
+    /// Ignored unless the distribution is built from a context.
+    #[must_use]
+    pub fn dockerfile(mut self, path: impl Into<PathBuf>) -> Self {
+        if let Source::Build { dockerfile, .. } = &mut self.source {
+            *dockerfile = Some(path.into());
+        }
+        self
+    }
+
+    /// The image a binary is copied into. Ignored unless the distribution
+    /// wraps a binary.
+    #[must_use]
+    pub fn base_image(mut self, image: impl Into<String>) -> Self {
+        if let Source::Binary { base_image, .. } = &mut self.source {
+            *base_image = image.into();
+        }
+        self
+    }
+
+    /// The directory inside the container that the harness writes configs
+    /// to and passes to the collector with `--config`.
+    #[must_use]
+    pub fn config_dir(mut self, dir: impl Into<String>) -> Self {
+        self.config_dir = dir.into();
+        self
+    }
+
+    /// Replaces the image of a published image. A built distribution keeps
+    /// its source and fails to resolve instead.
+    pub(crate) fn with_image(mut self, image: impl Into<String>) -> Self {
+        match &mut self.source {
+            Source::Image { image: current, .. } => *current = image.into(),
+            _ => self.invalid_override = Some("image"),
+        }
+        self
+    }
+
+    /// Replaces the tag of a published image. A built distribution keeps its
+    /// source and fails to resolve instead.
+    pub(crate) fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        match &mut self.source {
+            Source::Image { tag: current, .. } => *current = tag.into(),
+            _ => self.invalid_override = Some("tag"),
+        }
+        self
+    }
+
+    /// The `image:tag` this distribution runs as. For built distributions
+    /// the tag is a hash of the build inputs, which are read to compute it.
+    pub fn descriptor(&self) -> Result<String> {
+        let (image, tag) = self.image_and_tag()?;
+        Ok(format!("{image}:{tag}"))
+    }
+
+    fn image_and_tag(&self) -> Result<(String, String)> {
+        if let Some(setting) = self.invalid_override {
+            return Err(Error::BuiltDistributionOverride { setting });
+        }
+        let mut hasher = Sha256::new();
+        let name = match &self.source {
+            Source::Image { image, tag } => return Ok((image.clone(), tag.clone())),
+            Source::Build {
+                name,
+                context,
+                dockerfile,
+            } => {
+                hash_path(&mut hasher, context, context)?;
+                if let Some(dockerfile) = dockerfile {
+                    hash_field(&mut hasher, &std::fs::read(context.join(dockerfile))?);
+                }
+                name
+            }
+            Source::Binary {
+                name,
+                binary,
+                base_image,
+            } => {
+                hash_field(&mut hasher, &std::fs::read(binary)?);
+                hash_field(&mut hasher, binary_dockerfile(base_image).as_bytes());
+                name
+            }
+        };
+        Ok((
+            format!("{BUILT_IMAGE_PREFIX}/{name}"),
+            hasher.finalize()[..8]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+        ))
+    }
+
+    /// Builds the image if it does not exist yet.
+    pub(crate) async fn resolve(&self) -> Result<GenericImage> {
+        let (image, tag) = self.image_and_tag()?;
+        let buildable = match &self.source {
+            Source::Image { .. } => return Ok(GenericImage::new(image, tag)),
+            Source::Build {
+                context,
+                dockerfile,
+                ..
+            } => {
+                let buildable = GenericBuildableImage::new(image, tag).with_file(context, ".");
+                match dockerfile {
+                    Some(dockerfile) => buildable.with_dockerfile(context.join(dockerfile)),
+                    None => buildable,
+                }
+            }
+            Source::Binary {
+                binary, base_image, ..
+            } => GenericBuildableImage::new(image, tag)
+                .with_dockerfile_string(binary_dockerfile(base_image))
+                .with_file(binary, "otelcol"),
+        };
+
+        Ok(buildable
+            .build_image_with(BuildImageOptions::new().with_skip_if_exists(true))
+            .await?)
+    }
+}
+
+fn binary_dockerfile(base_image: &str) -> String {
+    format!(
+        "FROM {base_image}\n\
+         COPY --chmod=0755 otelcol /otelcol\n\
+         ENTRYPOINT [\"/otelcol\"]\n"
+    )
+}
+
+/// Hashes the relative paths and contents of every file under `path`, in
+/// a stable order.
+fn hash_path(hasher: &mut Sha256, root: &Path, path: &Path) -> Result<()> {
+    if path.is_dir() {
+        let mut entries = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+        for entry in entries {
+            hash_path(hasher, root, &entry)?;
+        }
+    } else {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        hash_field(hasher, relative.to_string_lossy().as_bytes());
+        hash_field(hasher, &std::fs::read(path)?);
+    }
+    Ok(())
+}
+
+/// Length-prefixes `bytes` so that adjacent fields cannot run together.
+fn hash_field(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+impl MetricKind {
+    fn parse(kind: &str) -> Self {
+        match kind {
+            "counter" => MetricKind::Counter,
+            "gauge" => MetricKind::Gauge,
+            "histogram" => MetricKind::Histogram,
+            "summary" => MetricKind::Summary,
+            _ => MetricKind::Untyped,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSample {
+    pub name: String,
+    pub kind: MetricKind,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+impl MetricSample {
+    /// Matches `name` exactly or with the `_total` suffix Prometheus adds
+    /// to counters, and requires every given label to be present.
+    pub fn matches(&self, name: &str, labels: &[(&str, &str)]) -> bool {
+        let name_matches = self.name == name
+            || self
+                .name
+                .strip_suffix("_total")
+                .is_some_and(|base| base == name);
+
+        name_matches
+            && labels
+                .iter()
+                .all(|(key, value)| self.labels.get(*key).is_some_and(|v| v == value))
+    }
+}
+
+/// A scrape of the collector's Prometheus self-metrics endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct InternalMetrics {
+    samples: Vec<MetricSample>,
+}
+
+impl InternalMetrics {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut kinds: HashMap<String, MetricKind> = HashMap::new();
+        let mut samples = Vec::new();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(comment) = line.strip_prefix('#') {
+                let mut parts = comment.split_whitespace();
+                if parts.next() == Some("TYPE")
+                    && let (Some(name), Some(kind)) = (parts.next(), parts.next())
+                {
+                    kinds.insert(name.to_string(), MetricKind::parse(kind));
+                }
+                continue;
+            }
+
+            let mut sample = parse_sample(line)?;
+            sample.kind = family_kind(&kinds, &sample.name);
+            samples.push(sample);
+        }
+
+        Ok(Self { samples })
+    }
+
+    pub fn samples(&self) -> &[MetricSample] {
+        &self.samples
+    }
+
+    pub fn find(&self, name: &str, labels: &[(&str, &str)]) -> Vec<&MetricSample> {
+        self.samples
+            .iter()
+            .filter(|sample| sample.matches(name, labels))
+            .collect()
+    }
+
+    /// Sums every series matching `name` and `labels`. Counters that have
+    /// never been incremented are absent from the scrape, so no match is 0.
+    pub fn sum(&self, name: &str, labels: &[(&str, &str)]) -> f64 {
+        self.find(name, labels)
+            .iter()
+            .map(|sample| sample.value)
+            .sum()
+    }
+}
+
+fn family_kind(kinds: &HashMap<String, MetricKind>, name: &str) -> MetricKind {
+    if let Some(kind) = kinds.get(name) {
+        return *kind;
+    }
+
+    ["_bucket", "_sum", "_count", "_total"]
+        .iter()
+        .filter_map(|suffix| name.strip_suffix(suffix))
+        .find_map(|base| kinds.get(base).copied())
+        .unwrap_or(MetricKind::Untyped)
+}
+
+fn parse_sample(line: &str) -> Result<MetricSample> {
+    let parse_error = |message: &str| Error::MetricsParse {
+        line: line.to_string(),
+        message: message.to_string(),
+    };
+
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .ok_or_else(|| parse_error("missing value"))?;
+    let name = &line[..name_end];
+    let mut rest = &line[name_end..];
+
+    let mut labels = BTreeMap::new();
+    if let Some(label_text) = rest.strip_prefix('{') {
+        let (parsed, remainder) = parse_labels(label_text).map_err(parse_error)?;
+        labels = parsed;
+        rest = remainder;
+    }
+
+    let value = rest
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| parse_error("missing value"))?;
+    let value = parse_value(value).ok_or_else(|| parse_error("invalid value"))?;
+
+    Ok(MetricSample {
+        name: name.to_string(),
+        kind: MetricKind::Untyped,
+        labels,
+        value,
+    })
+}
+
+fn parse_labels(text: &str) -> std::result::Result<(BTreeMap<String, String>, &str), &'static str> {
+    let mut labels = BTreeMap::new();
+    let mut chars = text.char_indices().peekable();
+
+    loop {
+        let separator = |(_, c): &(usize, char)| c.is_whitespace() || *c == ',';
+        while chars.next_if(separator).is_some() {}
+
+        match chars.peek() {
+            Some((i, '}')) => return Ok((labels, &text[i + 1..])),
+            None => return Err("unterminated label set"),
+            _ => {}
+        }
+
+        let mut key = String::new();
+        while let Some((_, c)) = chars.next_if(|(_, c)| *c != '=') {
+            key.push(c);
+        }
+        if chars.next().is_none() || chars.next().map(|(_, c)| c) != Some('"') {
+            return Err("expected quoted label value");
+        }
+
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                Some((_, '"')) => break,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, c)) => value.push(c),
+                    None => return Err("unterminated label value"),
+                },
+                Some((_, c)) => value.push(c),
+                None => return Err("unterminated label value"),
+            }
+        }
+
+        labels.insert(key.trim().to_string(), value);
+    }
+}
+
+fn parse_value(value: &str) -> Option<f64> {
+    match value {
+        "+Inf" | "Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => value.parse().ok(),
+    }
+}
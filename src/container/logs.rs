@@ -0,0 +1,247 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bollard::Docker;
+use bollard::container::LogOutput;
+use bollard::query_parameters::LogsOptionsBuilder;
+use futures_util::StreamExt;
+use regex::Regex;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{ChildStderr, ChildStdout};
+use tokio::task::JoinHandle;
+
+use crate::error::{Error, Result};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const ERROR_LEVELS: &[&str] = &["error", "dpanic", "panic", "fatal"];
+
+type Pipe = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Collector stdout and stderr, captured line by line for the lifetime of
+/// the container.
+#[derive(Debug, Clone, Default)]
+pub struct CollectorLogs {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl CollectorLogs {
+    pub(crate) fn attach(
+        &self,
+        docker: &Docker,
+        container_id: &str,
+        dump_on_drop: bool,
+    ) -> LogStream {
+        LogStream {
+            task: self.follow(docker, container_id, 0),
+            logs: self.clone(),
+            dump_on_drop,
+        }
+    }
+
+    /// Captures a local collector's output pipes.
+    pub(crate) fn capture(
+        &self,
+        stdout: ChildStdout,
+        stderr: ChildStderr,
+        dump_on_drop: bool,
+    ) -> LogStream {
+        LogStream {
+            task: self.read_pipes(stdout, stderr),
+            logs: self.clone(),
+            dump_on_drop,
+        }
+    }
+
+    fn read_pipes(&self, stdout: ChildStdout, stderr: ChildStderr) -> JoinHandle<()> {
+        let logs = self.clone();
+
+        tokio::spawn(async move {
+            let read = |pipe| {
+                let logs = logs.clone();
+                async move {
+                    let mut lines = BufReader::new(pipe).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        logs.push(&line);
+                    }
+                }
+            };
+            tokio::join!(
+                read(Box::new(stdout) as Pipe),
+                read(Box::new(stderr) as Pipe)
+            );
+        })
+    }
+
+    /// Streams lines logged from `since` (Unix seconds) onwards.
+    fn follow(&self, docker: &Docker, container_id: &str, since: i32) -> JoinHandle<()> {
+        let options = LogsOptionsBuilder::default()
+            .follow(true)
+            .stdout(true)
+            .stderr(true)
+            .since(since)
+            .build();
+        let mut stream = docker.logs(container_id, Some(options));
+        let logs = self.clone();
+
+        tokio::spawn(async move {
+            while let Some(Ok(output)) = stream.next().await {
+                if let LogOutput::StdOut { message } | LogOutput::StdErr { message } = output {
+                    logs.push(&String::from_utf8_lossy(&message));
+                }
+            }
+        })
+    }
+
+    fn push(&self, chunk: &str) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.extend(
+            chunk
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn text(&self) -> String {
+        self.lines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .join("\n")
+    }
+
+    pub fn contains(&self, needle: &str) -> bool {
+        self.lines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|line| line.contains(needle))
+    }
+
+    /// Like [`Self::contains`], ignoring the first `skip` lines.
+    pub(crate) fn contains_after(&self, skip: usize, needle: &str) -> bool {
+        self.lines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .skip(skip)
+            .any(|line| line.contains(needle))
+    }
+
+    /// Lines logged at `error` level or above.
+    pub fn errors(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|line| is_error_line(line))
+            .cloned()
+            .collect()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the collector has logged anything at `error` level or above.
+    pub fn assert_no_errors(&self) {
+        let errors = self.errors();
+        assert!(
+            errors.is_empty(),
+            "collector logged {} error(s):\n{}",
+            errors.len(),
+            errors.join("\n")
+        );
+    }
+
+    /// Waits for a line matching `pattern`, returning the first match.
+    pub async fn wait_for_line(&self, pattern: &str, timeout: Duration) -> Result<String> {
+        let regex = Regex::new(pattern)?;
+        let start = Instant::now();
+
+        loop {
+            let found = self
+                .lines
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .find(|line| regex.is_match(line))
+                .cloned();
+
+            if let Some(line) = found {
+                return Ok(line);
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(Error::LogLineTimeout {
+                    pattern: pattern.to_string(),
+                    timeout,
+                    logs: self.text(),
+                });
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Detects the level column of the collector's console encoding
+/// (`timestamp<TAB>level<TAB>caller<TAB>message`) and its JSON encoding.
+fn is_error_line(line: &str) -> bool {
+    if let Some(level) = line.split('\t').nth(1)
+        && ERROR_LEVELS.contains(&level.trim())
+    {
+        return true;
+    }
+
+    ERROR_LEVELS
+        .iter()
+        .any(|level| line.contains(&format!("\"level\":\"{level}\"")))
+}
+
+pub(crate) struct LogStream {
+    task: JoinHandle<()>,
+    logs: CollectorLogs,
+    dump_on_drop: bool,
+}
+
+impl LogStream {
+    /// Follows the container again after a restart, which ends the previous
+    /// stream. Lines from before `since` (Unix seconds) are not repeated.
+    pub(crate) fn reattach(&mut self, docker: &Docker, container_id: &str, since: i32) {
+        self.task.abort();
+        self.task = self.logs.follow(docker, container_id, since);
+    }
+
+    /// Captures the pipes of a restarted local collector.
+    pub(crate) fn recapture(&mut self, stdout: ChildStdout, stderr: ChildStderr) {
+        self.task.abort();
+        self.task = self.logs.read_pipes(stdout, stderr);
+    }
+}
+
+impl Drop for LogStream {
+    fn drop(&mut self) {
+        self.task.abort();
+
+        if self.dump_on_drop || std::thread::panicking() {
+            eprintln!(
+                "--- collector logs ---\n{}\n--- end collector logs ---",
+                self.logs.text()
+            );
+        }
+    }
+}
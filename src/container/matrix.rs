@@ -0,0 +1,183 @@
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use super::COLLECTOR_IMAGE;
+use crate::error::Result;
+
+/// A collector image and tag to run a scenario against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollectorVersion {
+    pub image: String,
+    pub tag: String,
+}
+
+impl CollectorVersion {
+    pub fn new(image: impl Into<String>, tag: impl Into<String>) -> Self {
+        Self {
+            image: image.into(),
+            tag: tag.into(),
+        }
+    }
+
+    /// A tag of the default contrib image.
+    pub fn contrib(tag: impl Into<String>) -> Self {
+        Self::new(COLLECTOR_IMAGE, tag)
+    }
+}
+
+impl fmt::Display for CollectorVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.image, self.tag)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionOutcome {
+    Passed,
+    /// The scenario returned an error.
+    Failed(String),
+    /// The scenario panicked, e.g. on a failed assertion.
+    Panicked(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct VersionResult {
+    pub version: CollectorVersion,
+    pub outcome: VersionOutcome,
+    pub duration: Duration,
+}
+
+impl VersionResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == VersionOutcome::Passed
+    }
+}
+
+/// Runs one scenario against several collector versions, so that an upgrade
+/// can be checked before the tag a test suite pins is moved.
+///
+/// Versions run one after another, each in its own task so that a panicking
+/// scenario is recorded against its version instead of ending the run.
+#[derive(Debug, Clone, Default)]
+pub struct VersionMatrix {
+    versions: Vec<CollectorVersion>,
+}
+
+impl VersionMatrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn version(mut self, image: impl Into<String>, tag: impl Into<String>) -> Self {
+        self.versions.push(CollectorVersion::new(image, tag));
+        self
+    }
+
+    /// Adds tags of the default contrib image.
+    #[must_use]
+    pub fn tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.versions
+            .extend(tags.into_iter().map(CollectorVersion::contrib));
+        self
+    }
+
+    /// Calls `scenario` once per version, typically passing the version to
+    /// [`CollectorTestHarnessBuilder::version`](super::CollectorTestHarnessBuilder::version).
+    pub async fn run<F, Fut>(&self, scenario: F) -> MatrixReport
+    where
+        F: Fn(CollectorVersion) -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let mut results = Vec::with_capacity(self.versions.len());
+
+        for version in &self.versions {
+            let start = Instant::now();
+            let outcome = match tokio::spawn(scenario(version.clone())).await {
+                Ok(Ok(())) => VersionOutcome::Passed,
+                Ok(Err(err)) => VersionOutcome::Failed(err.to_string()),
+                Err(err) => VersionOutcome::Panicked(panic_message(err)),
+            };
+            results.push(VersionResult {
+                version: version.clone(),
+                outcome,
+                duration: start.elapsed(),
+            });
+        }
+
+        MatrixReport { results }
+    }
+}
+
+fn panic_message(err: tokio::task::JoinError) -> String {
+    if !err.is_panic() {
+        return err.to_string();
+    }
+    let payload = err.into_panic();
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+/// Per-version results of [`VersionMatrix::run`], in the order the versions
+/// were added.
+#[derive(Debug, Clone)]
+pub struct MatrixReport {
+    results: Vec<VersionResult>,
+}
+
+impl MatrixReport {
+    pub fn results(&self) -> &[VersionResult] {
+        &self.results
+    }
+
+    pub fn result(&self, version: &CollectorVersion) -> Option<&VersionResult> {
+        self.results
+            .iter()
+            .find(|result| result.version == *version)
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(VersionResult::passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &VersionResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+
+    /// # Panics
+    ///
+    /// Panics with the full report if any version failed.
+    pub fn assert_all_passed(&self) {
+        assert!(
+            self.all_passed(),
+            "collector version matrix failed:\n{self}"
+        );
+    }
+}
+
+impl fmt::Display for MatrixReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            let status = match &result.outcome {
+                VersionOutcome::Passed => "ok".to_string(),
+                VersionOutcome::Failed(message) => format!("FAILED: {message}"),
+                VersionOutcome::Panicked(message) => format!("PANICKED: {message}"),
+            };
+            writeln!(
+                f,
+                "{} ({:.1}s) {status}",
+                result.version,
+                result.duration.as_secs_f64()
+            )?;
+        }
+        Ok(())
+    }
+}
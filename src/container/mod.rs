@@ -1,170 +1,1031 @@
+mod backend;
+mod cleanup;
+mod config;
+mod distribution;
+mod internal_metrics;
+mod logs;
+mod matrix;
+mod network;
+mod pool;
+mod ports;
+mod proxy;
+mod readiness;
+mod sink;
+mod storage;
+mod topology;
+mod validate;
+
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use mock_collector::{MockServer, Protocol, ServerHandle};
-use testcontainers::core::WaitFor;
-use testcontainers::runners::AsyncRunner;
-use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+use testcontainers::{CopyDataSource, GenericImage, ImageExt};
 
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, Signal};
+use backend::{ContainerBackend, LaunchSpec, ProcessBackend, WorkDir};
+use config::RenderedConfigs;
+use ports::{INTERNAL_METRICS_PORT, PortSpec, Ports};
+use readiness::{HealthCheck, Readiness};
+use sink::SinkSpec;
+use storage::StorageSpec;
 
-const CONTAINER_CONFIG_PATH: &str = "/etc/otelcol-contrib/config.yaml";
-const COLLECTOR_IMAGE: &str = "otel/opentelemetry-collector-contrib";
+pub use backend::{BINARY_ENV_VAR, BackendKind, CollectorBackend};
+pub use cleanup::{
+    DEFAULT_REAP_MIN_AGE, LABEL_HOST, LABEL_MANAGED, LABEL_PID, LABEL_RUN_ID, LABEL_TEST, host_id,
+    reap_stale_containers, reap_stale_containers_older_than, run_id,
+};
+pub use config::{CollectorConfig, ConfigSource};
+pub use distribution::{DEFAULT_BASE_IMAGE, DEFAULT_DISTRIBUTION_CONFIG_DIR, Distribution};
+pub use internal_metrics::{InternalMetrics, MetricKind, MetricSample};
+pub use logs::CollectorLogs;
+pub use matrix::{CollectorVersion, MatrixReport, VersionMatrix, VersionOutcome, VersionResult};
+pub use mock_collector::Protocol;
+pub use network::{GATEWAY_ALIAS, NetworkMode};
+pub use pool::{CollectorPool, PooledHarness};
+pub use ports::{HEALTH_CHECK_PORT, OTLP_GRPC_PORT, OTLP_HTTP_PORT};
+pub use proxy::FaultProxy;
+pub use readiness::DEFAULT_STARTUP_TIMEOUT;
+pub use sink::{DEFAULT_SINK, Fault, FaultInjector, Sink};
+pub use storage::{DurabilityReport, Storage};
+pub use topology::{TopologyHarness, TopologyHarnessBuilder};
+pub use validate::{ConfigIssue, ConfigValidator};
 
-#[cfg(target_os = "macos")]
-const DEFAULT_MOCK_HOST: &str = "host.docker.internal";
+pub(crate) const CONTAINER_CONFIG_DIR: &str = "/etc/otelcol-contrib";
+pub(crate) const COLLECTOR_IMAGE: &str = "otel/opentelemetry-collector-contrib";
+const INTERNAL_METRICS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const DUPLICATE_SETTLE_TIME: Duration = Duration::from_secs(1);
+const BIND_ATTEMPTS: usize = 3;
 
-#[cfg(not(target_os = "macos"))]
-const DEFAULT_MOCK_HOST: &str = "127.0.0.1";
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub fn find_free_port() -> Result<u16> {
     let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(Error::PortAllocation)?;
     Ok(listener.local_addr()?.port())
 }
 
+/// A suffix for Docker resources and temp paths that is unique across
+/// parallel tests and test processes.
+pub(crate) fn unique_suffix() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    format!(
+        "{}-{}-{nanos}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+fn internal_metrics_args(port: u16) -> Vec<String> {
+    vec![
+        "--config=yaml:service::telemetry::metrics::level: detailed".to_string(),
+        format!(
+            "--config=yaml:service::telemetry::metrics::readers: \
+             [{{pull: {{exporter: {{prometheus: {{host: 0.0.0.0, port: {port}}}}}}}}}]"
+        ),
+    ]
+}
+
 pub struct CollectorTestHarnessBuilder {
-    config_path: PathBuf,
-    exporter_endpoint_var: String,
-    mock_host: String,
-    image: String,
-    tag: String,
+    configs: Vec<ConfigSource>,
+    files: Vec<(String, CopyDataSource)>,
+    template_vars: HashMap<String, String>,
+    sinks: Vec<SinkSpec>,
+    mock_host: Option<String>,
+    network: NetworkMode,
+    distribution: Distribution,
     env_vars: HashMap<String, String>,
-    #[cfg(target_os = "macos")]
+    health_check: Option<HealthCheck>,
+    startup_timeout: Duration,
+    dump_logs_on_drop: bool,
+    validate_config: bool,
+    storage: Option<StorageSpec>,
     exposed_ports: Vec<u16>,
+    ports: Vec<PortSpec>,
+    test_name: Option<String>,
+    backend: BackendKind,
 }
 
 impl CollectorTestHarnessBuilder {
-    pub fn new(config_path: impl AsRef<Path>, exporter_endpoint_var: impl Into<String>) -> Self {
+    pub fn new(
+        config: impl Into<CollectorConfig>,
+        exporter_endpoint_var: impl Into<String>,
+    ) -> Self {
+        Self::from_config(config).sink(DEFAULT_SINK, Protocol::Grpc, exporter_endpoint_var)
+    }
+
+    /// Creates a builder without the default sink, for configs whose
+    /// exporters are all wired to named sinks.
+    pub fn from_config(config: impl Into<CollectorConfig>) -> Self {
         Self {
-            config_path: config_path.as_ref().to_path_buf(),
-            exporter_endpoint_var: exporter_endpoint_var.into(),
-            mock_host: DEFAULT_MOCK_HOST.to_string(),
-            image: COLLECTOR_IMAGE.to_string(),
-            tag: "latest".to_string(),
+            configs: vec![ConfigSource::Config(config.into())],
+            files: Vec::new(),
+            template_vars: HashMap::new(),
+            sinks: Vec::new(),
+            mock_host: None,
+            network: NetworkMode::default(),
+            distribution: Distribution::default(),
             env_vars: HashMap::new(),
-            #[cfg(target_os = "macos")]
+            health_check: None,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+            dump_logs_on_drop: false,
+            validate_config: false,
+            storage: None,
             exposed_ports: Vec::new(),
+            ports: Vec::new(),
+            test_name: cleanup::current_test_name(),
+            backend: BackendKind::from_env(),
+        }
+    }
+
+    /// Replaces every config source with `config`.
+    #[must_use]
+    pub fn config(mut self, config: impl Into<CollectorConfig>) -> Self {
+        self.configs = vec![ConfigSource::Config(config.into())];
+        self
+    }
+
+    /// Appends a config that is merged over the earlier sources.
+    #[must_use]
+    pub fn add_config(mut self, config: impl Into<CollectorConfig>) -> Self {
+        self.configs.push(ConfigSource::Config(config.into()));
+        self
+    }
+
+    /// Appends a config provider URI such as `env:OVERLAY`,
+    /// `yaml:processors::batch::timeout: 1s` or `http://host/config.yaml`.
+    #[must_use]
+    pub fn config_uri(mut self, uri: impl Into<String>) -> Self {
+        self.configs.push(ConfigSource::Uri(uri.into()));
+        self
+    }
+
+    /// Copies a file into the container before it starts, e.g. a secret
+    /// referenced from the config with `${file:/path}`.
+    #[must_use]
+    pub fn copy_to(
+        mut self,
+        container_path: impl Into<String>,
+        source: impl Into<CopyDataSource>,
+    ) -> Self {
+        self.files.push((container_path.into(), source.into()));
+        self
+    }
+
+    /// Sets a value for `{{ name }}` placeholders in the config. The harness
+    /// also provides `mock_host`, `config_dir`, `sink.<name>.endpoint`,
+    /// `sink.<name>.port`, `port.<name>` for allocated ports and, when enabled,
+    /// `internal_metrics_port` and `storage_dir`.
+    #[must_use]
+    pub fn template_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.template_vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Declares a mock sink whose endpoint is passed to the collector in
+    /// `endpoint_var`. Declaring a name twice replaces the earlier sink.
+    #[must_use]
+    pub fn sink(
+        mut self,
+        name: impl Into<String>,
+        protocol: Protocol,
+        endpoint_var: impl Into<String>,
+    ) -> Self {
+        let spec = SinkSpec {
+            name: name.into(),
+            protocol,
+            endpoint_var: endpoint_var.into(),
+            proxied: false,
+        };
+        self.sinks.retain(|existing| existing.name != spec.name);
+        self.sinks.push(spec);
+        self
+    }
+
+    /// Sets the protocol of the default sink created by [`Self::new`].
+    #[must_use]
+    pub fn sink_protocol(mut self, protocol: Protocol) -> Self {
+        if let Some(spec) = self.sinks.iter_mut().find(|spec| spec.name == DEFAULT_SINK) {
+            spec.protocol = protocol;
+        }
+        self
+    }
+
+    /// Routes the collector's traffic to the named sink through a
+    /// [`FaultProxy`], reachable from the harness with
+    /// [`CollectorTestHarness::proxy`].
+    #[must_use]
+    pub fn proxy(mut self, sink: &str) -> Self {
+        if let Some(spec) = self.sinks.iter_mut().find(|spec| spec.name == sink) {
+            spec.proxied = true;
         }
+        self
     }
 
     #[must_use]
     pub fn mock_host(mut self, host: impl Into<String>) -> Self {
-        self.mock_host = host.into();
+        self.mock_host = Some(host.into());
+        self
+    }
+
+    #[must_use]
+    pub fn network_mode(mut self, mode: NetworkMode) -> Self {
+        self.network = mode;
         self
     }
 
+    /// Shorthand for `network_mode(NetworkMode::Isolated)`.
+    #[must_use]
+    pub fn isolated_network(self) -> Self {
+        self.network_mode(NetworkMode::Isolated)
+    }
+
     #[must_use]
     pub fn image(mut self, image: impl Into<String>) -> Self {
-        self.image = image.into();
+        self.distribution = self.distribution.with_image(image);
         self
     }
 
     #[must_use]
     pub fn tag(mut self, tag: impl Into<String>) -> Self {
-        self.tag = tag.into();
+        self.distribution = self.distribution.with_tag(tag);
+        self
+    }
+
+    /// Runs a custom distribution, built on first use if it comes from a
+    /// Dockerfile or a local binary. Configs are written to its
+    /// [`config_dir`](Distribution::config_dir).
+    #[must_use]
+    pub fn distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
         self
     }
 
+    /// Sets both the image and the tag, e.g. from a [`VersionMatrix`].
+    #[must_use]
+    pub fn version(self, version: &CollectorVersion) -> Self {
+        self.image(&version.image).tag(&version.tag)
+    }
+
     #[must_use]
     pub fn env_var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.env_vars.insert(key.into(), value.into());
         self
     }
 
-    #[cfg(target_os = "macos")]
     #[must_use]
-    pub fn expose_port(mut self, port: u16) -> Self {
-        self.exposed_ports.push(port);
+    pub fn health_check_port(mut self, port: u16) -> Self {
+        self.ports.retain(|spec| spec.name != HEALTH_CHECK_PORT);
+        let path = self
+            .health_check
+            .take()
+            .map_or_else(|| "/".to_string(), |check| check.path);
+        self.health_check = Some(HealthCheck { port, path });
+        self.expose_port(port)
+    }
+
+    /// Allocates the health check port, passing it to the collector in
+    /// `env_var` for the `health_check` extension's endpoint.
+    #[must_use]
+    pub fn health_check_var(mut self, env_var: impl Into<String>) -> Self {
+        let path = self
+            .health_check
+            .take()
+            .map_or_else(|| "/".to_string(), |check| check.path);
+        self.health_check = Some(HealthCheck { port: 0, path });
+        self.port(HEALTH_CHECK_PORT, env_var)
+    }
+
+    #[must_use]
+    pub fn health_check_path(mut self, path: impl Into<String>) -> Self {
+        if let Some(check) = &mut self.health_check {
+            check.path = path.into();
+        }
         self
     }
 
-    #[cfg(not(target_os = "macos"))]
     #[must_use]
-    pub fn expose_port(self, _port: u16) -> Self {
+    pub fn startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
         self
     }
 
-    pub async fn start(self) -> Result<CollectorTestHarness> {
-        let mock_server = MockServer::builder()
-            .protocol(Protocol::Grpc)
-            .host(std::net::IpAddr::from([0, 0, 0, 0]))
-            .start()
-            .await
-            .map_err(|e| Error::MockServerStart(e.to_string()))?;
+    /// Prints the collector's logs when the harness is dropped. Logs are
+    /// always printed if the harness is dropped during a panic.
+    #[must_use]
+    pub fn dump_logs_on_drop(mut self, dump: bool) -> Self {
+        self.dump_logs_on_drop = dump;
+        self
+    }
 
-        let mock_port = mock_server.addr().port();
-        let mock_endpoint = format!("{}:{}", self.mock_host, mock_port);
+    /// Enables the collector's Prometheus self-metrics endpoint on a free
+    /// port, overriding `service.telemetry.metrics` from the config.
+    #[must_use]
+    pub fn internal_metrics(mut self) -> Self {
+        self.ports.retain(|spec| spec.name != INTERNAL_METRICS_PORT);
+        self.ports.push(PortSpec {
+            name: INTERNAL_METRICS_PORT.to_string(),
+            env_var: None,
+        });
+        self
+    }
 
-        let config_content = std::fs::read_to_string(&self.config_path)?;
+    /// Allocates a free port for a receiver, exposes it and passes it to the
+    /// collector in `env_var`. If another process takes the port before the
+    /// collector binds it, the harness allocates new ports and starts again.
+    /// On an isolated network the container port is fixed instead (4317,
+    /// 4318 and 13133 for the OTLP and health check ports) and published on
+    /// an ephemeral host port.
+    #[must_use]
+    pub fn port(mut self, name: impl Into<String>, env_var: impl Into<String>) -> Self {
+        let spec = PortSpec {
+            name: name.into(),
+            env_var: Some(env_var.into()),
+        };
+        self.ports.retain(|existing| existing.name != spec.name);
+        self.ports.push(spec);
+        self
+    }
 
-        let mut container = GenericImage::new(&self.image, &self.tag)
-            .with_wait_for(WaitFor::seconds(5))
-            .with_copy_to(CONTAINER_CONFIG_PATH, config_content.into_bytes())
-            .with_env_var(&self.exporter_endpoint_var, &mock_endpoint)
-            .with_startup_timeout(Duration::from_secs(30));
+    /// Allocates the OTLP receiver's gRPC and HTTP ports, for
+    /// [`CollectorTestHarness::otlp_grpc_endpoint`] and
+    /// [`CollectorTestHarness::otlp_http_endpoint`].
+    #[must_use]
+    pub fn otlp_receiver_ports(
+        self,
+        grpc_env_var: impl Into<String>,
+        http_env_var: impl Into<String>,
+    ) -> Self {
+        self.port(OTLP_GRPC_PORT, grpc_env_var)
+            .port(OTLP_HTTP_PORT, http_env_var)
+    }
 
-        #[cfg(target_os = "macos")]
-        {
-            for port in &self.exposed_ports {
-                container = container.with_mapped_port(*port, (*port).into());
-            }
+    /// Runs the image's `validate` subcommand against the rendered config
+    /// before starting the collector, failing with
+    /// [`Error::InvalidConfig`] instead of a startup timeout.
+    #[must_use]
+    pub fn validate_config(mut self) -> Self {
+        self.validate_config = true;
+        self
+    }
+
+    /// Mounts a fresh host temp directory at `container_path` for the
+    /// `file_storage` extension. The directory is kept across collector
+    /// restarts and removed when the harness is dropped. The path is
+    /// available to templated configs as `{{ storage_dir }}`.
+    #[must_use]
+    pub fn storage_dir(mut self, container_path: impl Into<String>) -> Self {
+        self.storage = Some(StorageSpec::TempDir {
+            container_path: container_path.into(),
+        });
+        self
+    }
+
+    /// Mounts the named Docker volume at `container_path` instead of a temp
+    /// directory. The volume is left in place when the harness is dropped.
+    #[must_use]
+    pub fn storage_volume(
+        mut self,
+        name: impl Into<String>,
+        container_path: impl Into<String>,
+    ) -> Self {
+        self.storage = Some(StorageSpec::Volume {
+            name: name.into(),
+            container_path: container_path.into(),
+        });
+        self
+    }
+
+    /// Names the test in the container's [`LABEL_TEST`] label. Defaults to
+    /// the name of the thread that created the builder, which is the test
+    /// name under the default test runner.
+    #[must_use]
+    pub fn test_name(mut self, name: impl Into<String>) -> Self {
+        self.test_name = Some(name.into());
+        self
+    }
+
+    /// Chooses where the collector runs. Defaults to
+    /// [`BackendKind::from_env`].
+    #[must_use]
+    pub fn backend(mut self, backend: BackendKind) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Runs `binary` (an `otelcol` or `otelcol-contrib` build) as a local
+    /// process instead of starting a container. Configs can refer to their
+    /// directory as `{{ config_dir }}`.
+    #[must_use]
+    pub fn local_binary(self, binary: impl Into<PathBuf>) -> Self {
+        self.backend(BackendKind::local_process(binary))
+    }
+
+    /// Makes a container port reachable from the host. See [`NetworkMode`]
+    /// for how it is published; use [`CollectorTestHarness::host_port`] to
+    /// find the host side.
+    #[must_use]
+    pub fn expose_port(mut self, port: u16) -> Self {
+        if !self.exposed_ports.contains(&port) {
+            self.exposed_ports.push(port);
         }
+        self
+    }
+
+    pub async fn start(self) -> Result<CollectorTestHarness> {
+        self.check_backend()?;
+        let target = match &self.backend {
+            BackendKind::Container => Target::Container(self.distribution.resolve().await?),
+            BackendKind::LocalProcess { binary } => Target::Process {
+                binary: binary.clone(),
+                workdir: Arc::new(WorkDir::create(&self.distribution.config_dir)?),
+            },
+        };
+        let mock_host = self.resolved_mock_host();
 
-        #[cfg(not(target_os = "macos"))]
-        {
-            container = container.with_network("host");
+        let mut sinks = Vec::with_capacity(self.sinks.len());
+        for spec in &self.sinks {
+            sinks.push(spec.start(&mock_host).await?);
         }
 
-        for (key, value) in &self.env_vars {
-            container = container.with_env_var(key, value);
+        let env = Environment {
+            mock_host,
+            sinks,
+            storage: self.storage.as_ref().map(StorageSpec::create).transpose()?,
+            config_dir: match &target {
+                Target::Container(_) => self.distribution.config_dir.clone(),
+                Target::Process { workdir, .. } => workdir.path().display().to_string(),
+            },
+            target,
+        };
+
+        if self.validate_config {
+            self.validate(&env).await?;
         }
 
-        let container = container.start().await?;
-        let container_id = container.id().to_string();
+        let mut attempt = 1;
+        let (backend, logs, mut ports) = loop {
+            let ports = Ports::allocate(&self.ports, self.network)?;
+            match self.launch(&env, &ports).await {
+                // Isolated containers bind in their own namespace, so only
+                // host mode can lose a port to another process.
+                Err(err)
+                    if self.network == NetworkMode::Host
+                        && attempt < BIND_ATTEMPTS
+                        && ports::is_bind_conflict(&err) =>
+                {
+                    attempt += 1;
+                }
+                result => {
+                    let (backend, logs) = result?;
+                    break (backend, logs, ports);
+                }
+            }
+        };
+        ports.resolve(backend.as_ref()).await?;
 
         Ok(CollectorTestHarness {
-            mock_server,
-            container,
-            container_id,
-            mock_host: self.mock_host,
+            sinks: env.sinks,
+            backend,
+            mock_host: env.mock_host,
+            network: self.network,
+            logs,
+            readiness: self.readiness(&ports),
+            storage: env.storage,
+            ports,
+            http: reqwest::Client::new(),
         })
     }
+
+    /// Leases a warm collector from [`CollectorPool::global`], starting one
+    /// if no identical collector is idle.
+    pub async fn start_pooled(self) -> Result<PooledHarness<'static>> {
+        CollectorPool::global().acquire(self).await
+    }
+
+    /// The address the collector uses to reach the host.
+    pub(crate) fn resolved_mock_host(&self) -> String {
+        match (&self.mock_host, &self.backend) {
+            (Some(host), _) => host.clone(),
+            (None, BackendKind::LocalProcess { .. }) => "127.0.0.1".to_string(),
+            (None, BackendKind::Container) => self.network.default_mock_host().to_string(),
+        }
+    }
+
+    fn check_backend(&self) -> Result<()> {
+        if let BackendKind::LocalProcess { .. } = self.backend {
+            let unsupported = if self.network == NetworkMode::Isolated {
+                Some("an isolated network")
+            } else if matches!(self.storage, Some(StorageSpec::Volume { .. })) {
+                Some("a named storage volume")
+            } else {
+                None
+            };
+            if let Some(feature) = unsupported {
+                return Err(Error::UnsupportedByBackend {
+                    feature: feature.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn validate(&self, env: &Environment) -> Result<()> {
+        let ports = Ports::allocate(&self.ports, self.network)?;
+        let rendered = self.render(env, &ports)?;
+        let vars = self.env(&env.sinks, &ports);
+
+        match &env.target {
+            Target::Container(image) => {
+                let mut request = image.clone().with_labels(self.labels());
+                for (key, value) in vars {
+                    request = request.with_env_var(key, value);
+                }
+                validate::run(request, &rendered, self.startup_timeout).await
+            }
+            Target::Process { binary, workdir } => {
+                ProcessBackend::validate(binary, workdir, &rendered, vars, self.startup_timeout)
+                    .await
+            }
+        }
+    }
+
+    /// Starts one collector on `ports` and waits for it to be ready.
+    async fn launch(
+        &self,
+        env: &Environment,
+        ports: &Ports,
+    ) -> Result<(Box<dyn CollectorBackend>, CollectorLogs)> {
+        let rendered = self.render(env, ports)?;
+
+        let mut args = rendered.args.clone();
+        if let Some(port) = ports.get(INTERNAL_METRICS_PORT) {
+            args.extend(internal_metrics_args(port.container_port));
+        }
+
+        let mut exposed_ports = self.exposed_ports.clone();
+        exposed_ports.extend(ports.iter().map(|port| port.container_port));
+
+        let logs = CollectorLogs::default();
+        let spec = LaunchSpec {
+            rendered: &rendered,
+            args,
+            env: self.env(&env.sinks, ports),
+            exposed_ports,
+            storage: env.storage.as_ref(),
+            logs: logs.clone(),
+            dump_logs_on_drop: self.dump_logs_on_drop,
+        };
+
+        let backend: Box<dyn CollectorBackend> = match &env.target {
+            Target::Container(image) => {
+                Box::new(ContainerBackend::launch(self, image.clone(), spec).await?)
+            }
+            Target::Process { binary, workdir } => {
+                Box::new(ProcessBackend::launch(binary, workdir.clone(), spec).await?)
+            }
+        };
+
+        self.readiness(ports)
+            .wait(backend.as_ref(), &logs, 0)
+            .await?;
+
+        Ok((backend, logs))
+    }
+
+    fn labels(&self) -> Vec<(String, String)> {
+        cleanup::labels(self.test_name.as_deref())
+    }
+
+    fn readiness(&self, ports: &Ports) -> Readiness {
+        let health_check = self.health_check.clone().map(|mut check| {
+            if let Some(port) = ports.get(HEALTH_CHECK_PORT) {
+                check.port = port.container_port;
+            }
+            check
+        });
+        Readiness::new(health_check, self.startup_timeout)
+    }
+
+    fn render(&self, env: &Environment, ports: &Ports) -> Result<RenderedConfigs> {
+        let template_values = self.template_values(env, ports);
+        RenderedConfigs::render(
+            &self.configs,
+            &self.files,
+            &template_values,
+            &env.config_dir,
+        )
+    }
+
+    /// Sink endpoints and allocated ports first, so that user env vars can
+    /// override them.
+    fn env(&self, sinks: &[Sink], ports: &Ports) -> Vec<(String, String)> {
+        let mut env = Vec::new();
+        for sink in sinks {
+            env.push((sink.endpoint_var().to_string(), sink.endpoint().to_string()));
+        }
+        for port in ports.iter() {
+            if let Some(env_var) = &port.env_var {
+                env.push((env_var.clone(), port.container_port.to_string()));
+            }
+        }
+        env.extend(self.env_vars.clone());
+        env
+    }
+
+    fn template_values(&self, env: &Environment, ports: &Ports) -> HashMap<String, String> {
+        let mut values = HashMap::from([
+            ("mock_host".to_string(), env.mock_host.clone()),
+            ("config_dir".to_string(), env.config_dir.clone()),
+        ]);
+
+        for sink in &env.sinks {
+            let port = sink
+                .proxy()
+                .map_or_else(|| sink.addr().port(), |proxy| proxy.addr().port());
+            values.insert(
+                format!("sink.{}.endpoint", sink.name()),
+                sink.endpoint().to_string(),
+            );
+            values.insert(format!("sink.{}.port", sink.name()), port.to_string());
+        }
+
+        for port in ports.iter() {
+            values.insert(
+                format!("port.{}", port.name),
+                port.container_port.to_string(),
+            );
+        }
+
+        if let Some(port) = ports.get(INTERNAL_METRICS_PORT) {
+            values.insert(
+                "internal_metrics_port".to_string(),
+                port.container_port.to_string(),
+            );
+        }
+
+        if let Some(storage_dir) = env.storage_dir() {
+            values.insert("storage_dir".to_string(), storage_dir);
+        }
+
+        values.extend(self.template_vars.clone());
+        values
+    }
+}
+
+/// What one start shares between config validation and every launch
+/// attempt.
+struct Environment {
+    mock_host: String,
+    sinks: Vec<Sink>,
+    storage: Option<Storage>,
+    config_dir: String,
+    target: Target,
+}
+
+enum Target {
+    Container(GenericImage),
+    Process {
+        binary: PathBuf,
+        /// Configs and copied files for the process.
+        workdir: Arc<WorkDir>,
+    },
+}
+
+impl Environment {
+    /// Where the collector sees the storage: the mount point in a container,
+    /// or the host directory for a local process.
+    fn storage_dir(&self) -> Option<String> {
+        let storage = self.storage.as_ref()?;
+        match (&self.target, storage.host_dir()) {
+            (Target::Process { .. }, Some(host_dir)) => Some(host_dir.display().to_string()),
+            _ => Some(storage.container_path().to_string()),
+        }
+    }
 }
 
 pub struct CollectorTestHarness {
-    mock_server: ServerHandle,
-    #[allow(dead_code)]
-    container: ContainerAsync<GenericImage>,
-    container_id: String,
+    sinks: Vec<Sink>,
+    backend: Box<dyn CollectorBackend>,
     mock_host: String,
+    network: NetworkMode,
+    logs: CollectorLogs,
+    readiness: Readiness,
+    storage: Option<Storage>,
+    ports: Ports,
+    http: reqwest::Client,
 }
 
 impl CollectorTestHarness {
     pub fn builder(
-        config_path: impl AsRef<Path>,
+        config: impl Into<CollectorConfig>,
         exporter_endpoint_var: impl Into<String>,
     ) -> CollectorTestHarnessBuilder {
-        CollectorTestHarnessBuilder::new(config_path, exporter_endpoint_var)
+        CollectorTestHarnessBuilder::new(config, exporter_endpoint_var)
     }
 
     pub fn mock_host(&self) -> &str {
         &self.mock_host
     }
 
+    /// The container ID, or a generated name when the collector runs as a
+    /// local process.
     pub fn container_id(&self) -> &str {
-        &self.container_id
+        self.backend.id()
     }
 
-    pub fn mock_server(&self) -> &ServerHandle {
-        &self.mock_server
+    pub fn backend(&self) -> &dyn CollectorBackend {
+        self.backend.as_ref()
     }
 
-    pub async fn shutdown(self) -> Result<()> {
-        self.mock_server
-            .shutdown()
+    pub fn network_mode(&self) -> NetworkMode {
+        self.network
+    }
+
+    /// The host port that reaches `container_port`, which must have been
+    /// exposed on the builder. In host mode the two are the same.
+    pub async fn host_port(&self, container_port: u16) -> Result<u16> {
+        self.backend.host_port(container_port).await
+    }
+
+    /// The host port allocated under `name` on the builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no port with that name was declared on the builder.
+    pub fn port(&self, name: &str) -> u16 {
+        self.ports
+            .get(name)
+            .unwrap_or_else(|| panic!("no port named {name:?} was declared"))
+            .host_port
+    }
+
+    /// The OTLP/gRPC receiver endpoint, from
+    /// [`CollectorTestHarnessBuilder::otlp_receiver_ports`].
+    pub fn otlp_grpc_endpoint(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port(OTLP_GRPC_PORT))
+    }
+
+    /// The OTLP/HTTP receiver base endpoint, from
+    /// [`CollectorTestHarnessBuilder::otlp_receiver_ports`].
+    pub fn otlp_http_endpoint(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port(OTLP_HTTP_PORT))
+    }
+
+    /// The OTLP/HTTP receiver endpoint for one signal, e.g. `/v1/traces`.
+    pub fn otlp_http_signal_endpoint(&self, signal: Signal) -> String {
+        format!("{}{}", self.otlp_http_endpoint(), sink::http_path(signal))
+    }
+
+    pub fn mock_server(&self) -> &Sink {
+        self.sink(DEFAULT_SINK)
+    }
+
+    /// Returns the sink declared under `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no sink with that name was declared on the builder.
+    pub fn sink(&self, name: &str) -> &Sink {
+        self.sinks
+            .iter()
+            .find(|sink| sink.name() == name)
+            .unwrap_or_else(|| panic!("no sink named {name:?} was declared"))
+    }
+
+    pub fn sinks(&self) -> impl Iterator<Item = &Sink> {
+        self.sinks.iter()
+    }
+
+    /// Returns the proxy in front of the sink declared under `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sink does not exist or was not proxied on the builder.
+    pub fn proxy(&self, name: &str) -> &FaultProxy {
+        self.sink(name)
+            .proxy()
+            .unwrap_or_else(|| panic!("sink {name:?} is not proxied"))
+    }
+
+    pub fn logs(&self) -> &CollectorLogs {
+        &self.logs
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the collector has logged anything at `error` level or above.
+    pub fn assert_no_errors_logged(&self) {
+        self.logs.assert_no_errors();
+    }
+
+    pub async fn wait_for_log_line(&self, pattern: &str, timeout: Duration) -> Result<String> {
+        self.logs.wait_for_line(pattern, timeout).await
+    }
+
+    /// The container port of the self-metrics endpoint.
+    pub fn internal_metrics_port(&self) -> Option<u16> {
+        self.ports
+            .get(INTERNAL_METRICS_PORT)
+            .map(|port| port.container_port)
+    }
+
+    pub async fn scrape_internal_metrics(&self) -> Result<InternalMetrics> {
+        let port = self
+            .ports
+            .get(INTERNAL_METRICS_PORT)
+            .ok_or(Error::InternalMetricsDisabled)?
+            .host_port;
+
+        let body = self
+            .http
+            .get(format!("http://127.0.0.1:{port}/metrics"))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        InternalMetrics::parse(&body)
+    }
+
+    /// Returns the sum of every series of `name` carrying `labels`.
+    pub async fn internal_metric(&self, name: &str, labels: &[(&str, &str)]) -> Result<f64> {
+        Ok(self.scrape_internal_metrics().await?.sum(name, labels))
+    }
+
+    pub async fn wait_for_internal_metric<F>(
+        &self,
+        name: &str,
+        labels: &[(&str, &str)],
+        predicate: F,
+        timeout: Duration,
+    ) -> Result<f64>
+    where
+        F: Fn(f64) -> bool,
+    {
+        let start = Instant::now();
+
+        loop {
+            let value = self.internal_metric(name, labels).await?;
+            if predicate(value) {
+                return Ok(value);
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(Error::InternalMetricTimeout {
+                    name: name.to_string(),
+                    timeout,
+                    value,
+                });
+            }
+
+            tokio::time::sleep(INTERNAL_METRICS_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Sends SIGTERM and waits up to `timeout` for the collector to drain
+    /// and exit before it is sent SIGKILL. Sinks stay up.
+    pub async fn stop(&self, timeout: Duration) -> Result<()> {
+        self.backend.stop(timeout).await
+    }
+
+    pub async fn kill(&self) -> Result<()> {
+        self.signal("SIGKILL").await
+    }
+
+    /// Sends `signal` (e.g. `"SIGTERM"` or `"SIGHUP"`) to the collector
+    /// without waiting for it to exit.
+    pub async fn signal(&self, signal: &str) -> Result<()> {
+        self.backend.signal(signal).await
+    }
+
+    pub async fn pause(&self) -> Result<()> {
+        self.backend.pause().await
+    }
+
+    pub async fn unpause(&self) -> Result<()> {
+        self.backend.unpause().await
+    }
+
+    /// Starts the same collector again, stopping it first if it is still
+    /// running, and waits for it to become ready. The config, environment
+    /// and sinks are unchanged.
+    pub async fn restart(&mut self) -> Result<()> {
+        if self.is_running().await? {
+            self.stop(DEFAULT_STOP_TIMEOUT).await?;
+        }
+
+        let skip_lines = self.logs.len();
+        self.backend.start().await?;
+        self.ports.resolve(self.backend.as_ref()).await?;
+
+        self.readiness
+            .wait(self.backend.as_ref(), &self.logs, skip_lines)
             .await
-            .map_err(|e| Error::MockServerShutdown(e.to_string()))?;
+    }
+
+    pub async fn is_running(&self) -> Result<bool> {
+        self.backend.is_running().await
+    }
+
+    /// The exit code of the last run, or `None` while the collector is running.
+    pub async fn exit_code(&self) -> Result<Option<i64>> {
+        self.backend.exit_code().await
+    }
+
+    pub async fn wait_for_exit(&self, timeout: Duration) -> Result<Option<i64>> {
+        let start = Instant::now();
+
+        while self.is_running().await? {
+            if start.elapsed() >= timeout {
+                return Err(Error::ExitTimeout { timeout });
+            }
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        }
+
+        self.exit_code().await
+    }
+
+    pub fn storage(&self) -> Option<&Storage> {
+        self.storage.as_ref()
+    }
+
+    /// Checks that a persistent queue delivers every item exactly once
+    /// across a crash. With the sink failing, `send` delivers `sent` items
+    /// of `signal` to the collector; once the exporter has tried and failed,
+    /// the collector is killed, the sink recovers and the collector is
+    /// restarted. Arrivals are counted until all items are seen or
+    /// `timeout` passes, plus a short settle time for late duplicates.
+    pub async fn check_durability<F, Fut>(
+        &mut self,
+        sink: &str,
+        signal: Signal,
+        sent: usize,
+        send: F,
+        timeout: Duration,
+    ) -> Result<DurabilityReport>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let faults = self.sink(sink).faults().clone();
+        faults.inject(Fault::unavailable());
+        send().await?;
+
+        let start = Instant::now();
+        while faults.faulted_requests() == 0 {
+            if start.elapsed() >= timeout {
+                faults.clear();
+                return Err(Error::SinkTimeout {
+                    sink: sink.to_string(),
+                    timeout,
+                });
+            }
+            tokio::time::sleep(DELIVERY_POLL_INTERVAL).await;
+        }
+
+        self.kill().await?;
+        self.wait_for_exit(timeout).await?;
+        faults.clear();
+        self.restart().await?;
+
+        let start = Instant::now();
+        loop {
+            let (_, unique) = storage::count_deliveries(self.sink(sink), signal).await;
+            if unique >= sent || start.elapsed() >= timeout {
+                break;
+            }
+            tokio::time::sleep(DELIVERY_POLL_INTERVAL).await;
+        }
+        tokio::time::sleep(DUPLICATE_SETTLE_TIME).await;
+
+        let (received, unique) = storage::count_deliveries(self.sink(sink), signal).await;
+        Ok(DurabilityReport {
+            sent,
+            received,
+            unique,
+        })
+    }
+
+    /// Clears the sinks and captured logs for the next pooled lease.
+    pub(crate) async fn reset(&self) {
+        for sink in &self.sinks {
+            sink.reset().await;
+        }
+        self.logs.clear();
+    }
+
+    pub async fn shutdown(self) -> Result<()> {
+        for sink in self.sinks {
+            sink.shutdown().await?;
+        }
         Ok(())
     }
 }
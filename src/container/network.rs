@@ -0,0 +1,90 @@
+use testcontainers::core::{Host, IntoContainerPort};
+use testcontainers::{ContainerAsync, ContainerRequest, GenericImage, ImageExt};
+
+use crate::error::Result;
+
+/// The hostname isolated collectors use to reach sinks on the host.
+pub const GATEWAY_ALIAS: &str = "host.docker.internal";
+
+#[cfg(target_os = "macos")]
+const HOST_MODE_MOCK_HOST: &str = "host.docker.internal";
+
+#[cfg(not(target_os = "macos"))]
+const HOST_MODE_MOCK_HOST: &str = "127.0.0.1";
+
+/// How the collector container is networked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NetworkMode {
+    /// Host networking on Linux. On macOS, exposed ports are mapped to the
+    /// same host port and sinks are reached through `host.docker.internal`.
+    /// Container ports are host ports, so parallel tests need distinct ports.
+    #[default]
+    Host,
+    /// A bridge network per harness. Exposed ports are mapped to ephemeral
+    /// host ports (see [`CollectorTestHarness::host_port`](super::CollectorTestHarness::host_port))
+    /// and sinks are reached through [`GATEWAY_ALIAS`], so fixed container
+    /// ports never clash between tests.
+    Isolated,
+}
+
+impl NetworkMode {
+    pub(crate) fn default_mock_host(self) -> &'static str {
+        match self {
+            NetworkMode::Host => HOST_MODE_MOCK_HOST,
+            NetworkMode::Isolated => GATEWAY_ALIAS,
+        }
+    }
+
+    /// Declares the ports to publish on ephemeral host ports in isolated
+    /// mode. Ports have to be declared on the image for Docker to publish
+    /// them without fixed host bindings.
+    pub(crate) fn expose(self, mut image: GenericImage, exposed_ports: &[u16]) -> GenericImage {
+        if self == NetworkMode::Isolated {
+            for port in exposed_ports {
+                image = image.with_exposed_port((*port).tcp());
+            }
+        }
+        image
+    }
+
+    pub(crate) fn apply(
+        self,
+        mut request: ContainerRequest<GenericImage>,
+        exposed_ports: &[u16],
+    ) -> ContainerRequest<GenericImage> {
+        match self {
+            NetworkMode::Host => {
+                #[cfg(target_os = "macos")]
+                {
+                    for port in exposed_ports {
+                        request = request.with_mapped_port(*port, (*port).tcp());
+                    }
+                }
+
+                #[cfg(not(target_os = "macos"))]
+                {
+                    let _ = exposed_ports;
+                    request = request.with_network("host");
+                }
+            }
+            NetworkMode::Isolated => {
+                request = request
+                    .with_network(format!("collector-tester-{}", super::unique_suffix()))
+                    .with_host(GATEWAY_ALIAS, Host::HostGateway);
+            }
+        }
+        request
+    }
+
+    /// The host port that reaches `container_port`.
+    pub(crate) async fn host_port(
+        self,
+        container: &ContainerAsync<GenericImage>,
+        container_port: u16,
+    ) -> Result<u16> {
+        match self {
+            NetworkMode::Host => Ok(container_port),
+            NetworkMode::Isolated => Ok(container.get_host_port_ipv4(container_port).await?),
+        }
+    }
+}
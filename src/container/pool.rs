@@ -0,0 +1,266 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, OnceLock};
+
+use testcontainers::CopyDataSource;
+use tokio::runtime::Runtime;
+
+use super::config::ConfigSource;
+use super::{CollectorTestHarness, CollectorTestHarnessBuilder};
+use crate::error::Result;
+
+const POOL_WORKER_THREADS: usize = 2;
+const DEFAULT_MAX_IDLE: usize = 4;
+
+/// Runs pooled collectors' sinks, log followers and Docker calls, which
+/// must outlive the per-test runtimes of `#[tokio::test]`.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(POOL_WORKER_THREADS)
+            .thread_name("collector-pool")
+            .enable_all()
+            .build()
+            .expect("failed to build collector pool runtime")
+    })
+}
+
+/// Keeps started collectors warm so that tests with the same setup skip
+/// container startup.
+///
+/// Idle collectors are kept by fingerprint: the image and tag, backend,
+/// network mode, the content of every config and copied file, env vars,
+/// sinks and ports. A collector is reused only by a builder with the same
+/// fingerprint, so tests with different setups each keep their own warm
+/// collector. At most [`max_idle`](Self::with_max_idle) collectors are kept;
+/// returning one more shuts down the one that has been idle longest. A
+/// collector is leased to one test at a time and returned when the lease is
+/// dropped; its sinks and captured logs are reset before the next lease.
+/// Collectors whose test panicked, or that were stopped, are discarded
+/// rather than reused.
+///
+/// The global pool is never dropped, so its idle collectors outlive the
+/// test process unless a test calls `CollectorPool::global().clear().await`
+/// at the end of the run. They carry the usual run labels, so
+/// [`reap_stale_containers`](super::reap_stale_containers) removes them on
+/// the next run otherwise.
+pub struct CollectorPool {
+    idle: Mutex<Vec<Idle>>,
+    max_idle: usize,
+}
+
+/// In the order they were returned, longest idle first.
+struct Idle {
+    fingerprint: u64,
+    harness: CollectorTestHarness,
+}
+
+impl Default for CollectorPool {
+    fn default() -> Self {
+        Self::with_max_idle(DEFAULT_MAX_IDLE)
+    }
+}
+
+impl CollectorPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A pool keeping at most `max_idle` collectors warm.
+    pub fn with_max_idle(max_idle: usize) -> Self {
+        Self {
+            idle: Mutex::default(),
+            max_idle,
+        }
+    }
+
+    /// The pool used by
+    /// [`CollectorTestHarnessBuilder::start_pooled`](super::CollectorTestHarnessBuilder::start_pooled).
+    pub fn global() -> &'static CollectorPool {
+        static GLOBAL: OnceLock<CollectorPool> = OnceLock::new();
+        GLOBAL.get_or_init(CollectorPool::new)
+    }
+
+    /// Leases the warm collector started from an identical builder, or
+    /// starts a new one.
+    pub async fn acquire(&self, builder: CollectorTestHarnessBuilder) -> Result<PooledHarness<'_>> {
+        let fingerprint = fingerprint(&builder)?;
+
+        if let Some(idle) = self.take_idle(fingerprint) {
+            let reused = runtime()
+                .spawn(async move {
+                    let harness = idle.harness;
+                    if harness.is_running().await.unwrap_or(false) {
+                        harness.reset().await;
+                        return Some(harness);
+                    }
+                    // Frees the host ports before the replacement starts.
+                    let _ = harness.shutdown().await;
+                    None
+                })
+                .await
+                .expect("collector pool task panicked");
+
+            if let Some(harness) = reused {
+                return Ok(self.lease(fingerprint, harness));
+            }
+        }
+
+        let harness = runtime()
+            .spawn(builder.start())
+            .await
+            .expect("collector pool task panicked")?;
+        Ok(self.lease(fingerprint, harness))
+    }
+
+    /// Collectors waiting to be leased.
+    pub fn idle_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Shuts down every idle collector. Collectors leased at the time are
+    /// returned to the pool as usual.
+    pub async fn clear(&self) -> Result<()> {
+        let harnesses: Vec<_> = self.lock().drain(..).map(|idle| idle.harness).collect();
+        runtime()
+            .spawn(async move {
+                for harness in harnesses {
+                    harness.shutdown().await?;
+                }
+                Ok(())
+            })
+            .await
+            .expect("collector pool task panicked")
+    }
+
+    fn lease(&self, fingerprint: u64, harness: CollectorTestHarness) -> PooledHarness<'_> {
+        PooledHarness {
+            pool: self,
+            fingerprint,
+            harness: Some(harness),
+        }
+    }
+
+    fn take_idle(&self, fingerprint: u64) -> Option<Idle> {
+        let mut idle = self.lock();
+        let index = idle
+            .iter()
+            .position(|idle| idle.fingerprint == fingerprint)?;
+        Some(idle.remove(index))
+    }
+
+    /// Keeps `harness` warm, discarding collectors it replaces or that no
+    /// longer fit under `max_idle`.
+    fn put_idle(&self, fingerprint: u64, harness: CollectorTestHarness) {
+        let mut idle = self.lock();
+        if let Some(index) = idle.iter().position(|idle| idle.fingerprint == fingerprint) {
+            discard(idle.remove(index).harness);
+        }
+        idle.push(Idle {
+            fingerprint,
+            harness,
+        });
+        let excess = idle.len().saturating_sub(self.max_idle);
+        for evicted in idle.drain(..excess) {
+            discard(evicted.harness);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Idle>> {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for CollectorPool {
+    fn drop(&mut self) {
+        for idle in self.lock().drain(..) {
+            discard(idle.harness);
+        }
+    }
+}
+
+/// Drops a collector on the pool runtime, which owns its sinks.
+fn discard(harness: CollectorTestHarness) {
+    runtime().spawn(async move {
+        let _ = harness.shutdown().await;
+    });
+}
+
+/// A collector leased from a [`CollectorPool`], returned to the pool when
+/// dropped.
+pub struct PooledHarness<'a> {
+    pool: &'a CollectorPool,
+    fingerprint: u64,
+    harness: Option<CollectorTestHarness>,
+}
+
+impl Deref for PooledHarness<'_> {
+    type Target = CollectorTestHarness;
+
+    fn deref(&self) -> &Self::Target {
+        self.harness.as_ref().expect("harness present until drop")
+    }
+}
+
+impl DerefMut for PooledHarness<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.harness.as_mut().expect("harness present until drop")
+    }
+}
+
+impl Drop for PooledHarness<'_> {
+    fn drop(&mut self) {
+        let Some(harness) = self.harness.take() else {
+            return;
+        };
+        if std::thread::panicking() {
+            discard(harness);
+            return;
+        }
+        self.pool.put_idle(self.fingerprint, harness);
+    }
+}
+
+/// Hashes everything in `builder` that affects the running container. File
+/// configs and copied files are hashed by content, so editing a config
+/// between runs of a long-lived pool starts a fresh collector.
+fn fingerprint(builder: &CollectorTestHarnessBuilder) -> Result<u64> {
+    let mut hasher = DefaultHasher::new();
+
+    builder.distribution.hash(&mut hasher);
+    builder.distribution.descriptor()?.hash(&mut hasher);
+    builder.backend.hash(&mut hasher);
+    builder.network.hash(&mut hasher);
+    builder.mock_host.hash(&mut hasher);
+
+    for source in &builder.configs {
+        match source {
+            ConfigSource::Config(config) => config.template()?.hash(&mut hasher),
+            ConfigSource::Uri(uri) => uri.hash(&mut hasher),
+        }
+    }
+    for (path, source) in &builder.files {
+        path.hash(&mut hasher);
+        match source {
+            CopyDataSource::File(file) => std::fs::read(file)?.hash(&mut hasher),
+            CopyDataSource::Data(data) => data.hash(&mut hasher),
+        }
+    }
+
+    sorted(&builder.template_vars).hash(&mut hasher);
+    sorted(&builder.env_vars).hash(&mut hasher);
+    builder.sinks.hash(&mut hasher);
+    builder.health_check.hash(&mut hasher);
+    builder.storage.hash(&mut hasher);
+    builder.ports.hash(&mut hasher);
+    builder.exposed_ports.hash(&mut hasher);
+
+    Ok(hasher.finish())
+}
+
+fn sorted(map: &HashMap<String, String>) -> BTreeMap<&String, &String> {
+    map.iter().collect()
+}
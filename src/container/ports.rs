@@ -0,0 +1,102 @@
+use super::backend::CollectorBackend;
+use super::find_free_port;
+use super::network::NetworkMode;
+use crate::error::{Error, Result};
+
+pub const OTLP_GRPC_PORT: &str = "otlp_grpc";
+pub const OTLP_HTTP_PORT: &str = "otlp_http";
+pub const HEALTH_CHECK_PORT: &str = "health_check";
+pub(crate) const INTERNAL_METRICS_PORT: &str = "internal_metrics";
+
+/// Where isolated containers put ports without a conventional number,
+/// counting up in declaration order.
+const FIRST_ISOLATED_PORT: u16 = 20000;
+
+const BIND_CONFLICT_MESSAGES: &[&str] = &["address already in use", "port is already allocated"];
+
+#[derive(Debug, Clone, Hash)]
+pub(crate) struct PortSpec {
+    pub(crate) name: String,
+    pub(crate) env_var: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AllocatedPort {
+    pub(crate) name: String,
+    pub(crate) env_var: Option<String>,
+    pub(crate) container_port: u16,
+    pub(crate) host_port: u16,
+}
+
+/// Ports allocated by the harness for one collector container.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Ports {
+    ports: Vec<AllocatedPort>,
+}
+
+impl Ports {
+    /// Free host ports in host mode. An isolated container has its own
+    /// network namespace, so it gets fixed container ports instead, which
+    /// are published on ephemeral host ports and resolved after start.
+    pub(crate) fn allocate(specs: &[PortSpec], network: NetworkMode) -> Result<Self> {
+        let mut ports: Vec<AllocatedPort> = Vec::with_capacity(specs.len());
+        let mut next_isolated = FIRST_ISOLATED_PORT;
+        for spec in specs {
+            let port = match network {
+                NetworkMode::Host => find_free_port()?,
+                NetworkMode::Isolated => isolated_port(&spec.name).unwrap_or_else(|| {
+                    next_isolated += 1;
+                    next_isolated - 1
+                }),
+            };
+            ports.push(AllocatedPort {
+                name: spec.name.clone(),
+                env_var: spec.env_var.clone(),
+                container_port: port,
+                host_port: port,
+            });
+        }
+        Ok(Self { ports })
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&AllocatedPort> {
+        self.ports.iter().find(|port| port.name == name)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &AllocatedPort> {
+        self.ports.iter()
+    }
+
+    /// Looks up the host side of each port, which changes when an isolated
+    /// container is restarted.
+    pub(crate) async fn resolve(&mut self, collector: &dyn CollectorBackend) -> Result<()> {
+        for port in &mut self.ports {
+            port.host_port = collector.host_port(port.container_port).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The collector's conventional port for a well-known port name.
+fn isolated_port(name: &str) -> Option<u16> {
+    match name {
+        OTLP_GRPC_PORT => Some(4317),
+        OTLP_HTTP_PORT => Some(4318),
+        HEALTH_CHECK_PORT => Some(13133),
+        INTERNAL_METRICS_PORT => Some(8888),
+        _ => None,
+    }
+}
+
+/// Whether a failed start was caused by another process taking one of the
+/// allocated ports between allocation and the collector binding it.
+pub(crate) fn is_bind_conflict(error: &Error) -> bool {
+    let text = match error {
+        Error::CollectorExited { logs, .. } => logs.clone(),
+        Error::TestContainers(error) => error.to_string(),
+        _ => return false,
+    };
+    BIND_CONFLICT_MESSAGES
+        .iter()
+        .any(|message| text.contains(message))
+}
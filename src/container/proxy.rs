@@ -0,0 +1,215 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::error::Result;
+
+const BUFFER_SIZE: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Mode {
+    #[default]
+    Pass,
+    Blackhole,
+    Reject,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ProxyState {
+    mode: Mode,
+    latency: Duration,
+    bytes_per_second: Option<u64>,
+    reset_generation: u64,
+}
+
+/// An in-process TCP proxy that simulates network faults between the
+/// collector and a backend.
+///
+/// Faults apply to open connections as well as new ones. A blackhole stops
+/// moving bytes without closing anything, so TCP buffers fill as they would
+/// during a partition and traffic resumes intact after [`Self::restore`].
+pub struct FaultProxy {
+    addr: SocketAddr,
+    target: SocketAddr,
+    state: watch::Sender<ProxyState>,
+    task: JoinHandle<()>,
+}
+
+impl FaultProxy {
+    pub async fn start(target: SocketAddr) -> Result<Self> {
+        Self::start_on(IpAddr::from([0, 0, 0, 0]), target).await
+    }
+
+    pub async fn start_on(host: IpAddr, target: SocketAddr) -> Result<Self> {
+        let listener = TcpListener::bind(SocketAddr::new(host, 0)).await?;
+        let addr = listener.local_addr()?;
+        let (state, _) = watch::channel(ProxyState::default());
+
+        let accept_state = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((client, _)) = listener.accept().await {
+                tokio::spawn(proxy_connection(client, target, accept_state.subscribe()));
+            }
+        });
+
+        Ok(Self {
+            addr,
+            target,
+            state,
+            task,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Stops forwarding in both directions until [`Self::restore`].
+    pub fn blackhole(&self) {
+        self.state.send_modify(|state| state.mode = Mode::Blackhole);
+    }
+
+    /// Resets open connections and every new connection until
+    /// [`Self::restore`].
+    pub fn reject_connections(&self) {
+        self.state.send_modify(|state| state.mode = Mode::Reject);
+    }
+
+    /// Resets the connections open now; later connections are unaffected.
+    pub fn reset_connections(&self) {
+        self.state.send_modify(|state| state.reset_generation += 1);
+    }
+
+    /// Delays every chunk forwarded in either direction.
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.send_modify(|state| state.latency = latency);
+    }
+
+    /// Limits each direction of each connection to `bytes_per_second`.
+    pub fn set_bandwidth(&self, bytes_per_second: u64) {
+        self.state
+            .send_modify(|state| state.bytes_per_second = Some(bytes_per_second.max(1)));
+    }
+
+    /// Clears every fault and network condition.
+    pub fn restore(&self) {
+        self.state.send_modify(|state| {
+            let reset_generation = state.reset_generation;
+            *state = ProxyState {
+                reset_generation,
+                ..ProxyState::default()
+            };
+        });
+    }
+}
+
+impl Drop for FaultProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn proxy_connection(
+    mut client: TcpStream,
+    target: SocketAddr,
+    state: watch::Receiver<ProxyState>,
+) {
+    let generation = state.borrow().reset_generation;
+    if state.borrow().mode == Mode::Reject {
+        reset(&client);
+        return;
+    }
+
+    let Ok(mut upstream) = TcpStream::connect(target).await else {
+        reset(&client);
+        return;
+    };
+    let _ = client.set_nodelay(true);
+    let _ = upstream.set_nodelay(true);
+
+    let (client_read, client_write) = client.split();
+    let (upstream_read, upstream_write) = upstream.split();
+
+    // Each direction closes on its own EOF, so a half-closed request still
+    // gets its response. A fault in either direction stops both.
+    let outcome = tokio::try_join!(
+        pump(client_read, upstream_write, state.clone(), generation),
+        pump(upstream_read, client_write, state, generation),
+    );
+
+    // The collector is the client, so faults reset its side of the
+    // connection whichever direction noticed them.
+    if let Err(Stop::Reset) = outcome {
+        reset(&client);
+    }
+}
+
+enum Stop {
+    Reset,
+    Closed,
+}
+
+impl From<io::Error> for Stop {
+    fn from(_: io::Error) -> Self {
+        Stop::Closed
+    }
+}
+
+async fn pump(
+    mut from: impl AsyncRead + Unpin,
+    mut to: impl AsyncWrite + Unpin,
+    mut state: watch::Receiver<ProxyState>,
+    generation: u64,
+) -> std::result::Result<(), Stop> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+
+    loop {
+        let conditions = state.borrow_and_update().clone();
+        if conditions.mode == Mode::Reject || conditions.reset_generation != generation {
+            return Err(Stop::Reset);
+        }
+
+        if conditions.mode == Mode::Blackhole {
+            state.changed().await.map_err(|_| Stop::Closed)?;
+            continue;
+        }
+
+        let read = tokio::select! {
+            read = from.read(&mut buffer) => read?,
+            changed = state.changed() => {
+                changed.map_err(|_| Stop::Closed)?;
+                continue;
+            }
+        };
+
+        if read == 0 {
+            to.shutdown().await?;
+            return Ok(());
+        }
+
+        if !conditions.latency.is_zero() {
+            tokio::time::sleep(conditions.latency).await;
+        }
+        if let Some(bytes_per_second) = conditions.bytes_per_second {
+            tokio::time::sleep(Duration::from_secs_f64(
+                read as f64 / bytes_per_second as f64,
+            ))
+            .await;
+        }
+
+        to.write_all(&buffer[..read]).await?;
+    }
+}
+
+fn reset(stream: &TcpStream) {
+    let _ = stream.set_zero_linger();
+}
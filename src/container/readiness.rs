@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use super::backend::CollectorBackend;
+use super::logs::CollectorLogs;
+use crate::error::{Error, Result};
+
+const READY_LOG_LINE: &str = "Everything is ready";
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Hash)]
+pub(crate) struct HealthCheck {
+    pub(crate) port: u16,
+    pub(crate) path: String,
+}
+
+/// Waits for the collector to report itself ready.
+///
+/// The `health_check` extension is probed when one is configured. Without
+/// one, the "Everything is ready" log line is waited for instead, so configs
+/// without the extension still start without a fixed sleep.
+pub(crate) struct Readiness {
+    health_check: Option<HealthCheck>,
+    startup_timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl Readiness {
+    pub(crate) fn new(health_check: Option<HealthCheck>, startup_timeout: Duration) -> Self {
+        Self {
+            health_check,
+            startup_timeout,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Only log lines after the first `skip_lines` count towards readiness,
+    /// so a restarted collector is not taken as ready from its earlier run.
+    pub(crate) async fn wait(
+        &self,
+        collector: &dyn CollectorBackend,
+        logs: &CollectorLogs,
+        skip_lines: usize,
+    ) -> Result<()> {
+        let start = Instant::now();
+
+        loop {
+            let ready = match &self.health_check {
+                Some(health_check) => self.health_check_passes(collector, health_check).await,
+                None => logs.contains_after(skip_lines, READY_LOG_LINE),
+            };
+            if ready {
+                return Ok(());
+            }
+
+            if !collector.is_running().await? {
+                return Err(Error::CollectorExited {
+                    exit_code: collector.exit_code().await?,
+                    logs: logs.text(),
+                });
+            }
+
+            if start.elapsed() >= self.startup_timeout {
+                return Err(Error::StartupTimeout {
+                    timeout: self.startup_timeout,
+                    logs: logs.text(),
+                });
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    async fn health_check_passes(
+        &self,
+        collector: &dyn CollectorBackend,
+        health_check: &HealthCheck,
+    ) -> bool {
+        let Ok(port) = collector.host_port(health_check.port).await else {
+            return false;
+        };
+
+        self.client
+            .get(format!("http://127.0.0.1:{port}{}", health_check.path))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success())
+    }
+}
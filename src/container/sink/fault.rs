@@ -0,0 +1,168 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A failure returned by a sink instead of accepting a request.
+///
+/// Error faults map to the gRPC status or HTTP response an OTLP backend
+/// would send: `Unavailable` is gRPC `UNAVAILABLE` or HTTP 503, and
+/// `ResourceExhausted` is gRPC `RESOURCE_EXHAUSTED` or HTTP 429. A
+/// `retry_after` is sent as `RetryInfo` on gRPC and `Retry-After` on HTTP.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    Unavailable {
+        retry_after: Option<Duration>,
+    },
+    ResourceExhausted {
+        retry_after: Option<Duration>,
+    },
+    /// Stores the request but reports `rejected` items in the OTLP
+    /// partial-success response.
+    PartialSuccess {
+        rejected: i64,
+        message: String,
+    },
+    /// Delays the response, then accepts the request.
+    Latency(Duration),
+}
+
+impl Fault {
+    pub fn unavailable() -> Self {
+        Fault::Unavailable { retry_after: None }
+    }
+
+    pub fn resource_exhausted() -> Self {
+        Fault::ResourceExhausted { retry_after: None }
+    }
+
+    pub fn partial_success(rejected: i64, message: impl Into<String>) -> Self {
+        Fault::PartialSuccess {
+            rejected,
+            message: message.into(),
+        }
+    }
+
+    pub fn latency(delay: Duration) -> Self {
+        Fault::Latency(delay)
+    }
+
+    #[must_use]
+    pub fn with_retry_after(self, delay: Duration) -> Self {
+        match self {
+            Fault::Unavailable { .. } => Fault::Unavailable {
+                retry_after: Some(delay),
+            },
+            Fault::ResourceExhausted { .. } => Fault::ResourceExhausted {
+                retry_after: Some(delay),
+            },
+            other => other,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ActiveFault {
+    fault: Fault,
+    remaining: Option<usize>,
+    until: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct FaultState {
+    active: Option<ActiveFault>,
+    requests: usize,
+    faulted: usize,
+}
+
+/// Controls the faults a [`Sink`](super::Sink) applies to incoming requests.
+///
+/// Each export request consults the injector once, so a schedule such as
+/// "fail the first three requests" counts requests rather than items.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    state: Arc<Mutex<FaultState>>,
+}
+
+impl FaultInjector {
+    /// Applies `fault` to every request until [`Self::clear`] is called.
+    pub fn inject(&self, fault: Fault) {
+        self.set(ActiveFault {
+            fault,
+            remaining: None,
+            until: None,
+        });
+    }
+
+    /// Applies `fault` to the next `count` requests, then recovers.
+    pub fn inject_for_requests(&self, fault: Fault, count: usize) {
+        self.set(ActiveFault {
+            fault,
+            remaining: Some(count),
+            until: None,
+        });
+    }
+
+    /// Applies `fault` to requests arriving within `duration`, then recovers.
+    pub fn inject_for(&self, fault: Fault, duration: Duration) {
+        self.set(ActiveFault {
+            fault,
+            remaining: None,
+            until: Some(Instant::now() + duration),
+        });
+    }
+
+    pub fn clear(&self) {
+        self.lock().active = None;
+    }
+
+    /// Clears the fault and the request counters.
+    pub fn reset(&self) {
+        *self.lock() = FaultState::default();
+    }
+
+    pub fn is_active(&self) -> bool {
+        let mut state = self.lock();
+        Self::expire(&mut state);
+        state.active.is_some()
+    }
+
+    /// Export requests received, whether or not they were faulted.
+    pub fn requests(&self) -> usize {
+        self.lock().requests
+    }
+
+    /// Export requests that had a fault applied.
+    pub fn faulted_requests(&self) -> usize {
+        self.lock().faulted
+    }
+
+    pub(crate) fn next_fault(&self) -> Option<Fault> {
+        let mut state = self.lock();
+        state.requests += 1;
+        Self::expire(&mut state);
+
+        let active = state.active.as_mut()?;
+        let fault = active.fault.clone();
+        if let Some(remaining) = &mut active.remaining {
+            *remaining -= 1;
+        }
+        state.faulted += 1;
+        Some(fault)
+    }
+
+    fn expire(state: &mut FaultState) {
+        let expired = state.active.as_ref().is_some_and(|active| {
+            active.remaining == Some(0) || active.until.is_some_and(|until| Instant::now() >= until)
+        });
+        if expired {
+            state.active = None;
+        }
+    }
+
+    fn set(&self, active: ActiveFault) {
+        self.lock().active = Some(active);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FaultState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
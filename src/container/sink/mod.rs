@@ -0,0 +1,222 @@
+mod fault;
+mod server;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+use mock_collector::{MockCollector, Protocol};
+use tokio::sync::RwLock;
+
+use super::proxy::FaultProxy;
+use crate::error::{Error, Result, Signal};
+use server::{HeaderRecorder, SinkServer, SinkState};
+
+pub use fault::{Fault, FaultInjector};
+
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub const DEFAULT_SINK: &str = "default";
+
+#[derive(Debug, Clone)]
+pub(crate) struct SinkSpec {
+    pub(crate) name: String,
+    pub(crate) protocol: Protocol,
+    pub(crate) endpoint_var: String,
+    pub(crate) proxied: bool,
+}
+
+// `Protocol` does not implement `Hash`, so its variant stands in for it.
+impl std::hash::Hash for SinkSpec {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        std::mem::discriminant(&self.protocol).hash(state);
+        self.endpoint_var.hash(state);
+        self.proxied.hash(state);
+    }
+}
+
+impl SinkSpec {
+    pub(crate) async fn start(&self, mock_host: &str) -> Result<Sink> {
+        let collector = Arc::new(RwLock::new(MockCollector::new()));
+        let faults = FaultInjector::default();
+        let headers = HeaderRecorder::default();
+        let state = SinkState {
+            collector: collector.clone(),
+            faults: faults.clone(),
+            headers: headers.clone(),
+        };
+        let server =
+            SinkServer::start(self.protocol, std::net::IpAddr::from([0, 0, 0, 0]), state).await?;
+
+        let proxy = if self.proxied {
+            let target = SocketAddr::new([127, 0, 0, 1].into(), server.addr().port());
+            Some(FaultProxy::start(target).await?)
+        } else {
+            None
+        };
+
+        let port = proxy
+            .as_ref()
+            .map_or_else(|| server.addr().port(), |proxy| proxy.addr().port());
+        let endpoint = format_endpoint(self.protocol, mock_host, port);
+
+        Ok(Sink {
+            name: self.name.clone(),
+            protocol: self.protocol,
+            endpoint_var: self.endpoint_var.clone(),
+            endpoint,
+            collector,
+            faults,
+            headers,
+            server,
+            proxy,
+        })
+    }
+}
+
+/// A mock OTLP backend owned by a [`CollectorTestHarness`](super::CollectorTestHarness).
+pub struct Sink {
+    name: String,
+    protocol: Protocol,
+    endpoint_var: String,
+    endpoint: String,
+    collector: Arc<RwLock<MockCollector>>,
+    faults: FaultInjector,
+    headers: HeaderRecorder,
+    server: SinkServer,
+    proxy: Option<FaultProxy>,
+}
+
+impl Sink {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.server.addr()
+    }
+
+    pub fn endpoint_var(&self) -> &str {
+        &self.endpoint_var
+    }
+
+    /// The endpoint the collector was given to reach this sink, which is the
+    /// proxy's address when the sink is proxied.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// The endpoint for a single signal, including the OTLP/HTTP path for
+    /// exporters configured with `traces_endpoint` and friends.
+    pub fn signal_endpoint(&self, signal: Signal) -> String {
+        match self.protocol {
+            Protocol::Grpc => self.endpoint.clone(),
+            Protocol::HttpBinary | Protocol::HttpJson => {
+                format!("{}{}", self.endpoint, http_path(signal))
+            }
+        }
+    }
+
+    /// Controls the errors, throttling and latency this sink applies to
+    /// incoming export requests.
+    pub fn faults(&self) -> &FaultInjector {
+        &self.faults
+    }
+
+    /// The headers of the latest export of `signal`, or its gRPC metadata,
+    /// as sent: `Content-Encoding` and `grpc-encoding` show the compression.
+    pub fn last_headers(&self, signal: Signal) -> Option<HeaderMap> {
+        self.headers.last(signal)
+    }
+
+    /// The proxy between the collector and this sink, if one was requested
+    /// with [`CollectorTestHarnessBuilder::proxy`](super::CollectorTestHarnessBuilder::proxy).
+    pub fn proxy(&self) -> Option<&FaultProxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Discards everything received, including recorded headers, and clears
+    /// faults, including any network faults on the proxy.
+    pub async fn reset(&self) {
+        self.collector.write().await.clear();
+        self.faults.reset();
+        self.headers.clear();
+        if let Some(proxy) = &self.proxy {
+            proxy.restore();
+        }
+    }
+
+    pub async fn with_collector<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&MockCollector) -> R,
+    {
+        f(&*self.collector.read().await)
+    }
+
+    pub async fn with_collector_mut<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut MockCollector) -> R,
+    {
+        f(&mut *self.collector.write().await)
+    }
+
+    pub async fn wait_until<F>(&self, predicate: F, timeout: Duration) -> Result<()>
+    where
+        F: Fn(&MockCollector) -> bool,
+    {
+        let start = Instant::now();
+
+        loop {
+            if predicate(&*self.collector.read().await) {
+                return Ok(());
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(Error::SinkTimeout {
+                    sink: self.name.clone(),
+                    timeout,
+                });
+            }
+
+            tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+        }
+    }
+
+    pub async fn wait_for_spans(&self, count: usize, timeout: Duration) -> Result<()> {
+        self.wait_until(|c| c.span_count() >= count, timeout).await
+    }
+
+    pub async fn wait_for_metrics(&self, count: usize, timeout: Duration) -> Result<()> {
+        self.wait_until(|c| c.metric_count() >= count, timeout)
+            .await
+    }
+
+    pub async fn wait_for_logs(&self, count: usize, timeout: Duration) -> Result<()> {
+        self.wait_until(|c| c.log_count() >= count, timeout).await
+    }
+
+    pub async fn shutdown(self) -> Result<()> {
+        self.server.shutdown().await
+    }
+}
+
+pub(crate) fn format_endpoint(protocol: Protocol, host: &str, port: u16) -> String {
+    match protocol {
+        Protocol::Grpc => format!("{host}:{port}"),
+        Protocol::HttpBinary | Protocol::HttpJson => format!("http://{host}:{port}"),
+    }
+}
+
+pub(crate) fn http_path(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Traces => "/v1/traces",
+        Signal::Metrics => "/v1/metrics",
+        Signal::Logs => "/v1/logs",
+    }
+}
@@ -0,0 +1,456 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use bytes::Bytes;
+use mock_collector::{MockCollector, Protocol};
+use opentelemetry_proto::tonic::collector::logs::v1::logs_service_server::{
+    LogsService, LogsServiceServer,
+};
+use opentelemetry_proto::tonic::collector::logs::v1::{
+    ExportLogsPartialSuccess, ExportLogsServiceRequest, ExportLogsServiceResponse,
+};
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_server::{
+    MetricsService, MetricsServiceServer,
+};
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::{
+    TraceService, TraceServiceServer,
+};
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use prost::Message;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::net::TcpListener;
+use tokio::sync::{RwLock, oneshot};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::codec::CompressionEncoding;
+use tower_http::decompression::RequestDecompressionLayer;
+
+use super::fault::{Fault, FaultInjector};
+use super::http_path;
+use crate::error::{Error, Result, Signal};
+
+trait ExportRequest: Message + Default + DeserializeOwned + Send + 'static {
+    type Response: Message + Serialize + Send + 'static;
+
+    const SIGNAL: Signal;
+
+    fn store(self, collector: &mut MockCollector);
+
+    fn response(partial_success: Option<(i64, String)>) -> Self::Response;
+}
+
+impl ExportRequest for ExportTraceServiceRequest {
+    type Response = ExportTraceServiceResponse;
+
+    const SIGNAL: Signal = Signal::Traces;
+
+    fn store(self, collector: &mut MockCollector) {
+        collector.add_traces(self);
+    }
+
+    fn response(partial_success: Option<(i64, String)>) -> Self::Response {
+        ExportTraceServiceResponse {
+            partial_success: partial_success.map(|(rejected, error_message)| {
+                ExportTracePartialSuccess {
+                    rejected_spans: rejected,
+                    error_message,
+                }
+            }),
+        }
+    }
+}
+
+impl ExportRequest for ExportMetricsServiceRequest {
+    type Response = ExportMetricsServiceResponse;
+
+    const SIGNAL: Signal = Signal::Metrics;
+
+    fn store(self, collector: &mut MockCollector) {
+        collector.add_metrics(self);
+    }
+
+    fn response(partial_success: Option<(i64, String)>) -> Self::Response {
+        ExportMetricsServiceResponse {
+            partial_success: partial_success.map(|(rejected, error_message)| {
+                ExportMetricsPartialSuccess {
+                    rejected_data_points: rejected,
+                    error_message,
+                }
+            }),
+        }
+    }
+}
+
+impl ExportRequest for ExportLogsServiceRequest {
+    type Response = ExportLogsServiceResponse;
+
+    const SIGNAL: Signal = Signal::Logs;
+
+    fn store(self, collector: &mut MockCollector) {
+        collector.add_logs(self);
+    }
+
+    fn response(partial_success: Option<(i64, String)>) -> Self::Response {
+        ExportLogsServiceResponse {
+            partial_success: partial_success.map(|(rejected, error_message)| {
+                ExportLogsPartialSuccess {
+                    rejected_log_records: rejected,
+                    error_message,
+                }
+            }),
+        }
+    }
+}
+
+/// The headers, or gRPC metadata, of the latest export of each signal, as
+/// sent and so before decompression.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeaderRecorder(Arc<Mutex<Vec<(Signal, HeaderMap)>>>);
+
+impl HeaderRecorder {
+    fn record(&self, signal: Signal, headers: HeaderMap) {
+        let mut latest = self.lock();
+        latest.retain(|(recorded, _)| *recorded != signal);
+        latest.push((signal, headers));
+    }
+
+    pub(crate) fn last(&self, signal: Signal) -> Option<HeaderMap> {
+        self.lock()
+            .iter()
+            .find(|(recorded, _)| *recorded == signal)
+            .map(|(_, headers)| headers.clone())
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Signal, HeaderMap)>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct SinkState {
+    pub(crate) collector: Arc<RwLock<MockCollector>>,
+    pub(crate) faults: FaultInjector,
+    pub(crate) headers: HeaderRecorder,
+}
+
+impl SinkState {
+    /// Applies the next scheduled fault, returning it as the error when the
+    /// request should be refused.
+    async fn export<R: ExportRequest>(
+        &self,
+        request: R,
+    ) -> std::result::Result<R::Response, Fault> {
+        let partial_success = match self.faults.next_fault() {
+            None => None,
+            Some(Fault::Latency(delay)) => {
+                tokio::time::sleep(delay).await;
+                None
+            }
+            Some(Fault::PartialSuccess { rejected, message }) => Some((rejected, message)),
+            Some(fault) => return Err(fault),
+        };
+
+        request.store(&mut *self.collector.write().await);
+        Ok(R::response(partial_success))
+    }
+}
+
+pub(crate) struct SinkServer {
+    addr: SocketAddr,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<std::result::Result<(), String>>>,
+}
+
+impl SinkServer {
+    pub(crate) async fn start(protocol: Protocol, host: IpAddr, state: SinkState) -> Result<Self> {
+        let listener = TcpListener::bind(SocketAddr::new(host, 0))
+            .await
+            .map_err(|e| Error::MockServerStart(e.to_string()))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| Error::MockServerStart(e.to_string()))?;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let shutdown = async {
+            shutdown_rx.await.ok();
+        };
+
+        let task = match protocol {
+            Protocol::Grpc => {
+                let sink = GrpcSink { state };
+                tokio::spawn(async move {
+                    tonic::transport::Server::builder()
+                        .add_service(
+                            TraceServiceServer::new(sink.clone())
+                                .accept_compressed(CompressionEncoding::Gzip)
+                                .accept_compressed(CompressionEncoding::Zstd),
+                        )
+                        .add_service(
+                            MetricsServiceServer::new(sink.clone())
+                                .accept_compressed(CompressionEncoding::Gzip)
+                                .accept_compressed(CompressionEncoding::Zstd),
+                        )
+                        .add_service(
+                            LogsServiceServer::new(sink)
+                                .accept_compressed(CompressionEncoding::Gzip)
+                                .accept_compressed(CompressionEncoding::Zstd),
+                        )
+                        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+                        .await
+                        .map_err(|e| e.to_string())
+                })
+            }
+            Protocol::HttpBinary | Protocol::HttpJson => {
+                let sink = HttpSink {
+                    state,
+                    json: protocol == Protocol::HttpJson,
+                };
+                let app = Router::new()
+                    .route("/v1/traces", post(http_export::<ExportTraceServiceRequest>))
+                    .route(
+                        "/v1/metrics",
+                        post(http_export::<ExportMetricsServiceRequest>),
+                    )
+                    .route("/v1/logs", post(http_export::<ExportLogsServiceRequest>))
+                    .layer(RequestDecompressionLayer::new())
+                    .layer(middleware::from_fn_with_state(sink.clone(), record_headers))
+                    .with_state(sink);
+                tokio::spawn(async move {
+                    axum::serve(listener, app)
+                        .with_graceful_shutdown(shutdown)
+                        .await
+                        .map_err(|e| e.to_string())
+                })
+            }
+        };
+
+        Ok(Self {
+            addr,
+            shutdown_tx: Some(shutdown_tx),
+            task: Some(task),
+        })
+    }
+
+    pub(crate) fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub(crate) async fn shutdown(mut self) -> Result<()> {
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+        match self.task.take() {
+            Some(task) => task
+                .await
+                .map_err(|e| Error::MockServerShutdown(e.to_string()))?
+                .map_err(Error::MockServerShutdown),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for SinkServer {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+#[derive(Clone)]
+struct GrpcSink {
+    state: SinkState,
+}
+
+impl GrpcSink {
+    async fn export<R: ExportRequest>(
+        &self,
+        request: tonic::Request<R>,
+    ) -> std::result::Result<tonic::Response<R::Response>, tonic::Status> {
+        self.state
+            .headers
+            .record(R::SIGNAL, request.metadata().clone().into_headers());
+        self.state
+            .export(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(grpc_status)
+    }
+}
+
+#[tonic::async_trait]
+impl TraceService for GrpcSink {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportTraceServiceRequest>,
+    ) -> std::result::Result<tonic::Response<ExportTraceServiceResponse>, tonic::Status> {
+        GrpcSink::export(self, request).await
+    }
+}
+
+#[tonic::async_trait]
+impl MetricsService for GrpcSink {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportMetricsServiceRequest>,
+    ) -> std::result::Result<tonic::Response<ExportMetricsServiceResponse>, tonic::Status> {
+        GrpcSink::export(self, request).await
+    }
+}
+
+#[tonic::async_trait]
+impl LogsService for GrpcSink {
+    async fn export(
+        &self,
+        request: tonic::Request<ExportLogsServiceRequest>,
+    ) -> std::result::Result<tonic::Response<ExportLogsServiceResponse>, tonic::Status> {
+        GrpcSink::export(self, request).await
+    }
+}
+
+fn grpc_status(fault: Fault) -> tonic::Status {
+    let (code, retry_after) = match fault {
+        Fault::ResourceExhausted { retry_after } => (tonic::Code::ResourceExhausted, retry_after),
+        Fault::Unavailable { retry_after } => (tonic::Code::Unavailable, retry_after),
+        _ => (tonic::Code::Unavailable, None),
+    };
+    let message = "fault injected by collector-tester";
+
+    match retry_after {
+        Some(delay) => {
+            let details = RpcStatus {
+                code: code as i32,
+                message: message.to_string(),
+                details: vec![RpcAny {
+                    type_url: "type.googleapis.com/google.rpc.RetryInfo".to_string(),
+                    value: RetryInfo {
+                        retry_delay: Some(RpcDuration {
+                            seconds: delay.as_secs() as i64,
+                            nanos: delay.subsec_nanos() as i32,
+                        }),
+                    }
+                    .encode_to_vec(),
+                }],
+            };
+            tonic::Status::with_details(code, message, Bytes::from(details.encode_to_vec()))
+        }
+        None => tonic::Status::new(code, message),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<RpcAny>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RpcAny {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    retry_delay: Option<RpcDuration>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RpcDuration {
+    #[prost(int64, tag = "1")]
+    seconds: i64,
+    #[prost(int32, tag = "2")]
+    nanos: i32,
+}
+
+#[derive(Clone)]
+struct HttpSink {
+    state: SinkState,
+    json: bool,
+}
+
+impl HttpSink {
+    fn decode<R: ExportRequest>(&self, body: &[u8]) -> std::result::Result<R, String> {
+        if self.json {
+            serde_json::from_slice(body).map_err(|e| e.to_string())
+        } else {
+            R::decode(body).map_err(|e| e.to_string())
+        }
+    }
+
+    fn encode<T: Message + Serialize>(&self, message: &T) -> Response {
+        let (content_type, body) = if self.json {
+            (
+                "application/json",
+                serde_json::to_vec(message).unwrap_or_default(),
+            )
+        } else {
+            ("application/x-protobuf", message.encode_to_vec())
+        };
+        ([(header::CONTENT_TYPE, content_type)], body).into_response()
+    }
+}
+
+/// Runs outside the decompression layer, which strips `Content-Encoding`.
+async fn record_headers(State(sink): State<HttpSink>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let signal = [Signal::Traces, Signal::Metrics, Signal::Logs]
+        .into_iter()
+        .find(|&signal| http_path(signal) == path);
+    if let Some(signal) = signal {
+        sink.state.headers.record(signal, request.headers().clone());
+    }
+    next.run(request).await
+}
+
+async fn http_export<R: ExportRequest>(State(sink): State<HttpSink>, body: Bytes) -> Response {
+    let request = match sink.decode::<R>(&body) {
+        Ok(request) => request,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    match sink.state.export(request).await {
+        Ok(response) => sink.encode(&response),
+        Err(fault) => http_fault_response(fault),
+    }
+}
+
+fn http_fault_response(fault: Fault) -> Response {
+    let (status, retry_after) = match fault {
+        Fault::ResourceExhausted { retry_after } => (StatusCode::TOO_MANY_REQUESTS, retry_after),
+        Fault::Unavailable { retry_after } => (StatusCode::SERVICE_UNAVAILABLE, retry_after),
+        _ => (StatusCode::SERVICE_UNAVAILABLE, None),
+    };
+
+    let mut response = status.into_response();
+    if let Some(delay) = retry_after {
+        let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+    }
+    response
+}
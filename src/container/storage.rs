@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use prost::Message;
+use testcontainers::core::Mount;
+
+use super::{Sink, unique_suffix};
+use crate::error::{Result, Signal};
+
+#[derive(Debug, Clone, Hash)]
+pub(crate) enum StorageSpec {
+    TempDir {
+        container_path: String,
+    },
+    Volume {
+        name: String,
+        container_path: String,
+    },
+}
+
+impl StorageSpec {
+    pub(crate) fn create(&self) -> Result<Storage> {
+        match self {
+            StorageSpec::TempDir { container_path } => {
+                let host_dir = std::env::temp_dir()
+                    .join(format!("collector-tester-storage-{}", unique_suffix()));
+                std::fs::create_dir_all(&host_dir)?;
+
+                // The collector image runs as an unprivileged user.
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(&host_dir, std::fs::Permissions::from_mode(0o777))?;
+                }
+
+                Ok(Storage {
+                    container_path: container_path.clone(),
+                    host_dir: Some(host_dir),
+                    volume: None,
+                })
+            }
+            StorageSpec::Volume {
+                name,
+                container_path,
+            } => Ok(Storage {
+                container_path: container_path.clone(),
+                host_dir: None,
+                volume: Some(name.clone()),
+            }),
+        }
+    }
+}
+
+/// Storage mounted into the collector for the `file_storage` extension.
+/// It lives as long as the harness, so it survives collector restarts.
+#[derive(Debug)]
+pub struct Storage {
+    container_path: String,
+    host_dir: Option<PathBuf>,
+    volume: Option<String>,
+}
+
+impl Storage {
+    pub fn container_path(&self) -> &str {
+        &self.container_path
+    }
+
+    /// The host directory backing the storage, removed when the harness is
+    /// dropped. `None` for a named volume.
+    pub fn host_dir(&self) -> Option<&Path> {
+        self.host_dir.as_deref()
+    }
+
+    pub fn volume(&self) -> Option<&str> {
+        self.volume.as_deref()
+    }
+
+    pub(crate) fn mount(&self) -> Mount {
+        match (&self.host_dir, &self.volume) {
+            (Some(dir), _) => Mount::bind_mount(dir.to_string_lossy(), &self.container_path),
+            (None, Some(volume)) => Mount::volume_mount(volume, &self.container_path),
+            (None, None) => unreachable!("storage has neither a host dir nor a volume"),
+        }
+    }
+}
+
+impl Drop for Storage {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.host_dir {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// What a sink received after a crash-and-restart durability check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurabilityReport {
+    pub sent: usize,
+    pub received: usize,
+    pub unique: usize,
+}
+
+impl DurabilityReport {
+    pub fn duplicates(&self) -> usize {
+        self.received - self.unique
+    }
+
+    pub fn lost(&self) -> usize {
+        self.sent.saturating_sub(self.unique)
+    }
+
+    /// # Panics
+    ///
+    /// Panics unless every item sent arrived exactly once.
+    pub fn assert_exactly_once(&self) {
+        assert!(
+            self.lost() == 0 && self.duplicates() == 0 && self.unique == self.sent,
+            "expected {} items exactly once, received {} ({} unique, {} lost, {} duplicates)",
+            self.sent,
+            self.received,
+            self.unique,
+            self.lost(),
+            self.duplicates()
+        );
+    }
+}
+
+/// Counts items of `signal` at `sink`, and how many are distinct. Items are
+/// compared by their encoded protobuf, so a redelivered item is a duplicate.
+pub(crate) async fn count_deliveries(sink: &Sink, signal: Signal) -> (usize, usize) {
+    sink.with_collector(|collector| {
+        let encoded: Vec<Vec<u8>> = match signal {
+            Signal::Traces => collector
+                .spans()
+                .iter()
+                .map(|span| span.span().encode_to_vec())
+                .collect(),
+            Signal::Metrics => collector
+                .metrics()
+                .iter()
+                .map(|metric| metric.metric().encode_to_vec())
+                .collect(),
+            Signal::Logs => collector
+                .logs()
+                .iter()
+                .map(|log| log.log_record().encode_to_vec())
+                .collect(),
+        };
+        let unique = encoded.iter().collect::<HashSet<_>>().len();
+        (encoded.len(), unique)
+    })
+    .await
+}
@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use mock_collector::Protocol;
+
+use super::ports::{OTLP_GRPC_PORT, OTLP_HTTP_PORT};
+use super::sink::{self, Sink};
+use super::{CollectorTestHarness, CollectorTestHarnessBuilder, DEFAULT_STOP_TIMEOUT};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone)]
+struct Forward {
+    from: String,
+    to: String,
+    protocol: Protocol,
+    endpoint_var: String,
+}
+
+/// Builds a chain of collectors, such as agents forwarding to a gateway
+/// tier, where each node is an ordinary [`CollectorTestHarnessBuilder`].
+///
+/// Mock sinks belong to the node builders, so the leaves of the topology
+/// export to sinks while the inner nodes export to each other.
+#[derive(Default)]
+pub struct TopologyHarnessBuilder {
+    nodes: Vec<(String, CollectorTestHarnessBuilder)>,
+    forwards: Vec<Forward>,
+}
+
+impl TopologyHarnessBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a collector under `name`, replacing any node of the same name.
+    #[must_use]
+    pub fn node(mut self, name: impl Into<String>, builder: CollectorTestHarnessBuilder) -> Self {
+        let name = name.into();
+        self.nodes.retain(|(existing, _)| *existing != name);
+        self.nodes.push((name, builder));
+        self
+    }
+
+    /// Points `from` at the OTLP receiver of `to`, passing the endpoint in
+    /// `endpoint_var`. `to` must declare
+    /// [`otlp_receiver_ports`](CollectorTestHarnessBuilder::otlp_receiver_ports),
+    /// and is started before `from`.
+    #[must_use]
+    pub fn forward(
+        mut self,
+        from: impl Into<String>,
+        to: impl Into<String>,
+        protocol: Protocol,
+        endpoint_var: impl Into<String>,
+    ) -> Self {
+        self.forwards.push(Forward {
+            from: from.into(),
+            to: to.into(),
+            protocol,
+            endpoint_var: endpoint_var.into(),
+        });
+        self
+    }
+
+    /// Starts every node, downstream nodes first, so that each collector's
+    /// exporters have somewhere to connect to when it starts.
+    pub async fn start(self) -> Result<TopologyHarness> {
+        let order = self.start_order()?;
+        let mut builders: Vec<_> = self.nodes.into_iter().map(Some).collect();
+        let mut nodes: Vec<(String, CollectorTestHarness)> = Vec::with_capacity(order.len());
+
+        for index in order {
+            let (name, mut builder) = builders[index].take().expect("node started twice");
+            let mock_host = builder.resolved_mock_host();
+
+            for forward in self.forwards.iter().filter(|forward| forward.from == name) {
+                let (_, downstream) = nodes
+                    .iter()
+                    .find(|(node, _)| *node == forward.to)
+                    .expect("downstream node started first");
+                let port_name = match forward.protocol {
+                    Protocol::Grpc => OTLP_GRPC_PORT,
+                    Protocol::HttpBinary | Protocol::HttpJson => OTLP_HTTP_PORT,
+                };
+                let port =
+                    downstream
+                        .ports
+                        .get(port_name)
+                        .ok_or_else(|| Error::MissingReceiverPort {
+                            node: forward.to.clone(),
+                            port: port_name,
+                        })?;
+                let endpoint = sink::format_endpoint(forward.protocol, &mock_host, port.host_port);
+                builder = builder.env_var(&forward.endpoint_var, endpoint);
+            }
+
+            nodes.push((name, builder.start().await?));
+        }
+
+        Ok(TopologyHarness { nodes })
+    }
+
+    /// Node indices ordered so that every node comes after the nodes it
+    /// forwards to, keeping declaration order otherwise.
+    fn start_order(&self) -> Result<Vec<usize>> {
+        let index_of = |name: &str| {
+            self.nodes
+                .iter()
+                .position(|(node, _)| node == name)
+                .ok_or_else(|| Error::UnknownNode {
+                    node: name.to_string(),
+                })
+        };
+
+        let mut downstream: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        for forward in &self.forwards {
+            let from = index_of(&forward.from)?;
+            downstream[from].push(index_of(&forward.to)?);
+        }
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut started = vec![false; self.nodes.len()];
+        while order.len() < self.nodes.len() {
+            let ready = (0..self.nodes.len())
+                .find(|&node| !started[node] && downstream[node].iter().all(|&next| started[next]));
+            let Some(node) = ready else {
+                let nodes = (0..self.nodes.len())
+                    .filter(|&node| !started[node])
+                    .map(|node| self.nodes[node].0.clone())
+                    .collect();
+                return Err(Error::TopologyCycle { nodes });
+            };
+            started[node] = true;
+            order.push(node);
+        }
+        Ok(order)
+    }
+}
+
+/// A set of running collectors wired to each other by
+/// [`TopologyHarnessBuilder`].
+pub struct TopologyHarness {
+    /// In start order, downstream nodes first.
+    nodes: Vec<(String, CollectorTestHarness)>,
+}
+
+impl TopologyHarness {
+    pub fn builder() -> TopologyHarnessBuilder {
+        TopologyHarnessBuilder::new()
+    }
+
+    /// Returns the collector started under `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no node of that name was declared.
+    pub fn node(&self, name: &str) -> &CollectorTestHarness {
+        self.nodes
+            .iter()
+            .find(|(node, _)| node == name)
+            .map(|(_, harness)| harness)
+            .unwrap_or_else(|| panic!("no topology node named {name:?}"))
+    }
+
+    /// Mutable access for lifecycle operations such as
+    /// [`CollectorTestHarness::restart`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no node of that name was declared.
+    pub fn node_mut(&mut self, name: &str) -> &mut CollectorTestHarness {
+        self.nodes
+            .iter_mut()
+            .find(|(node, _)| node == name)
+            .map(|(_, harness)| harness)
+            .unwrap_or_else(|| panic!("no topology node named {name:?}"))
+    }
+
+    /// Every node in start order, downstream nodes first.
+    pub fn nodes(&self) -> impl Iterator<Item = (&str, &CollectorTestHarness)> {
+        self.nodes
+            .iter()
+            .map(|(name, harness)| (name.as_str(), harness))
+    }
+
+    /// Returns the sink declared as `sink` on node `node`.
+    pub fn sink(&self, node: &str, sink: &str) -> &Sink {
+        self.node(node).sink(sink)
+    }
+
+    /// Stops the collectors upstream first with SIGTERM, waiting up to
+    /// `timeout` for each, so that agents flush what they have queued into
+    /// gateways that are still running. Sinks keep running, so what arrived
+    /// can still be inspected. Every node is stopped even if an earlier one
+    /// fails; the first error is returned.
+    pub async fn stop(&self, timeout: Duration) -> Result<()> {
+        let mut result = Ok(());
+        for (_, harness) in self.nodes.iter().rev() {
+            let stop = Self::stop_node(harness, timeout).await;
+            if result.is_ok() {
+                result = stop;
+            }
+        }
+        result
+    }
+
+    /// Stops each collector as [`stop`](Self::stop) does, then shuts down
+    /// its sinks, so a node's sinks outlive the nodes exporting to it.
+    pub async fn shutdown(self) -> Result<()> {
+        let mut result = Ok(());
+        for (_, harness) in self.nodes.into_iter().rev() {
+            let stop = Self::stop_node(&harness, DEFAULT_STOP_TIMEOUT).await;
+            let shutdown = harness.shutdown().await;
+            if result.is_ok() {
+                result = stop.and(shutdown);
+            }
+        }
+        result
+    }
+
+    async fn stop_node(harness: &CollectorTestHarness, timeout: Duration) -> Result<()> {
+        if harness.is_running().await? {
+            harness.stop(timeout).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerRequest, CopyDataSource, GenericImage, ImageExt};
+
+use super::cleanup;
+use super::config::{ConfigSource, RenderedConfigs};
+use super::{CollectorConfig, DEFAULT_STARTUP_TIMEOUT, Distribution};
+use crate::error::{Error, Result};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static PIPELINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"service::pipelines::([^:\s]+)").unwrap());
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"references (receiver|processor|exporter|connector|extension) "([^"]+)""#).unwrap()
+});
+static COMPONENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^'?(receivers|processors|exporters|connectors|extensions)::([^':\s]+)").unwrap()
+});
+static UNKNOWN_TYPE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"'(receivers|processors|exporters|connectors|extensions)' unknown type: "[^"]*" for id: "([^"]+)""#,
+    )
+    .unwrap()
+});
+static LOG_TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2} ").unwrap());
+
+/// One problem reported by `otelcol validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// The component at fault, as `<kind>s::<id>`, e.g. `exporters::otlp/missing`.
+    pub component: Option<String>,
+    pub pipeline: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(pipeline) = &self.pipeline {
+            write!(f, "[pipeline {pipeline}] ")?;
+        }
+        if let Some(component) = &self.component {
+            write!(f, "[{component}] ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl ConfigIssue {
+    /// Extracts issues from the output of `otelcol validate`, which prints
+    /// `Error: ...` followed by any continuation lines, then repeats the
+    /// error in a timestamped log line.
+    pub fn parse(output: &str) -> Vec<Self> {
+        output
+            .lines()
+            .skip_while(|line| !line.starts_with("Error:"))
+            .take_while(|line| !LOG_TIMESTAMP.is_match(line))
+            .filter_map(|line| Self::parse_line(line.trim()))
+            .collect()
+    }
+
+    fn parse_line(line: &str) -> Option<Self> {
+        let message = line.strip_prefix("Error:").unwrap_or(line).trim();
+        let message = message
+            .strip_prefix("invalid configuration:")
+            .unwrap_or(message)
+            .trim();
+        if message.is_empty() || message.ends_with(':') {
+            return None;
+        }
+
+        let pipeline = PIPELINE
+            .captures(message)
+            .map(|captures| captures[1].to_string());
+        let component = REFERENCE
+            .captures(message)
+            .map(|captures| format!("{}s::{}", &captures[1], &captures[2]))
+            .or_else(|| {
+                [&*COMPONENT, &*UNKNOWN_TYPE].iter().find_map(|regex| {
+                    regex
+                        .captures(message)
+                        .map(|captures| format!("{}::{}", &captures[1], &captures[2]))
+                })
+            });
+
+        Some(Self {
+            component,
+            pipeline,
+            message: message.to_string(),
+        })
+    }
+}
+
+/// Validates configs with an image's `validate` subcommand, without
+/// starting a collector or any sinks.
+#[derive(Debug, Clone)]
+pub struct ConfigValidator {
+    distribution: Distribution,
+    env_vars: HashMap<String, String>,
+    template_vars: HashMap<String, String>,
+    files: Vec<(String, CopyDataSource)>,
+    timeout: Duration,
+}
+
+impl Default for ConfigValidator {
+    fn default() -> Self {
+        Self {
+            distribution: Distribution::default(),
+            env_vars: HashMap::new(),
+            template_vars: HashMap::new(),
+            files: Vec::new(),
+            timeout: DEFAULT_STARTUP_TIMEOUT,
+        }
+    }
+}
+
+impl ConfigValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn image(mut self, image: impl Into<String>) -> Self {
+        self.distribution = self.distribution.with_image(image);
+        self
+    }
+
+    #[must_use]
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.distribution = self.distribution.with_tag(tag);
+        self
+    }
+
+    #[must_use]
+    pub fn distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    #[must_use]
+    pub fn env_var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env_vars.insert(key.into(), value.into());
+        self
+    }
+
+    #[must_use]
+    pub fn template_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.template_vars.insert(name.into(), value.into());
+        self
+    }
+
+    #[must_use]
+    pub fn copy_to(
+        mut self,
+        container_path: impl Into<String>,
+        source: impl Into<CopyDataSource>,
+    ) -> Self {
+        self.files.push((container_path.into(), source.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub async fn validate(&self, config: impl Into<CollectorConfig>) -> Result<()> {
+        self.validate_sources(&[ConfigSource::Config(config.into())])
+            .await
+    }
+
+    /// Validates the merged result of `sources`, as the harness would pass
+    /// them to the collector.
+    pub async fn validate_sources(&self, sources: &[ConfigSource]) -> Result<()> {
+        let config_dir = &self.distribution.config_dir;
+        let mut template_vars = HashMap::from([("config_dir".to_string(), config_dir.clone())]);
+        template_vars.extend(self.template_vars.clone());
+        let rendered = RenderedConfigs::render(sources, &self.files, &template_vars, config_dir)?;
+
+        let mut request = self
+            .distribution
+            .resolve()
+            .await?
+            .with_labels(cleanup::labels(cleanup::current_test_name().as_deref()));
+        for (key, value) in &self.env_vars {
+            request = request.with_env_var(key, value);
+        }
+
+        run(request, &rendered, self.timeout).await
+    }
+
+    /// Validates every `.yaml` and `.yml` file in `dir` on its own,
+    /// returning each file's result in path order.
+    pub async fn validate_dir(&self, dir: impl AsRef<Path>) -> Result<Vec<(PathBuf, Result<()>)>> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_yaml = path
+                .extension()
+                .is_some_and(|extension| extension == "yaml" || extension == "yml");
+            if is_yaml && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut results = Vec::with_capacity(paths.len());
+        for path in paths {
+            let result = self.validate(path.as_path()).await;
+            results.push((path, result));
+        }
+        Ok(results)
+    }
+}
+
+pub(crate) async fn run(
+    request: ContainerRequest<GenericImage>,
+    rendered: &RenderedConfigs,
+    timeout: Duration,
+) -> Result<()> {
+    let mut args = vec!["validate".to_string()];
+    args.extend(rendered.args.iter().cloned());
+
+    let container = rendered.copy_into(request).with_cmd(args).start().await?;
+    let start = Instant::now();
+
+    while container.is_running().await? {
+        if start.elapsed() >= timeout {
+            return Err(Error::ValidationTimeout { timeout });
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    let exit_code = container.exit_code().await?;
+    if exit_code == Some(0) {
+        return Ok(());
+    }
+
+    let output = format!(
+        "{}{}",
+        String::from_utf8_lossy(&container.stdout_to_vec().await?),
+        String::from_utf8_lossy(&container.stderr_to_vec().await?)
+    );
+    Err(invalid_config(exit_code, output))
+}
+
+/// The error for a failed `validate` run, with the issues parsed from its
+/// output.
+pub(crate) fn invalid_config(exit_code: Option<i64>, output: String) -> Error {
+    let mut issues = ConfigIssue::parse(&output);
+    if issues.is_empty() {
+        issues.push(ConfigIssue {
+            component: None,
+            pipeline: None,
+            message: format!("validate exited with code {exit_code:?}"),
+        });
+    }
+
+    Error::InvalidConfig { issues, output }
+}
@@ -1,5 +1,9 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
+use crate::container::ConfigIssue;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("failed to build {signal} exporter: {message}")]
@@ -11,6 +15,9 @@ pub enum Error {
     #[error("failed to shutdown {signal}: {message}")]
     Shutdown { signal: Signal, message: String },
 
+    #[error("failed to send {signal} over OTLP: {message}")]
+    OtlpSend { signal: Signal, message: String },
+
     #[error("failed to find free port: {0}")]
     PortAllocation(#[source] std::io::Error),
 
@@ -20,12 +27,100 @@ pub enum Error {
     #[error("failed to shutdown mock server: {0}")]
     MockServerShutdown(String),
 
+    #[error("timed out after {timeout:?} waiting on sink {sink:?}")]
+    SinkTimeout { sink: String, timeout: Duration },
+
+    #[error("collector not ready after {timeout:?}\n--- collector logs ---\n{logs}")]
+    StartupTimeout { timeout: Duration, logs: String },
+
+    #[error(
+        "collector exited during startup with code {exit_code:?}\n--- collector logs ---\n{logs}"
+    )]
+    CollectorExited {
+        exit_code: Option<i64>,
+        logs: String,
+    },
+
+    #[error("no log line matching {pattern:?} after {timeout:?}\n--- collector logs ---\n{logs}")]
+    LogLineTimeout {
+        pattern: String,
+        timeout: Duration,
+        logs: String,
+    },
+
+    #[error("invalid log pattern: {0}")]
+    InvalidLogPattern(#[from] regex::Error),
+
+    #[error("internal metrics were not enabled on the harness builder")]
+    InternalMetricsDisabled,
+
+    #[error("failed to parse metrics line {line:?}: {message}")]
+    MetricsParse { line: String, message: String },
+
+    #[error("internal metric {name} was {value} after {timeout:?}")]
+    InternalMetricTimeout {
+        name: String,
+        timeout: Duration,
+        value: f64,
+    },
+
+    #[error("no value for config placeholder {{{{ {placeholder} }}}}")]
+    ConfigTemplate { placeholder: String },
+
+    #[error("YAML error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("invalid collector config:{}", format_issues(.issues))]
+    InvalidConfig {
+        issues: Vec<ConfigIssue>,
+        output: String,
+    },
+
+    #[error("config validation did not finish within {timeout:?}")]
+    ValidationTimeout { timeout: Duration },
+
+    #[error("collector still running after {timeout:?}")]
+    ExitTimeout { timeout: Duration },
+
+    #[error("cannot set the {setting} of a locally built distribution")]
+    BuiltDistributionOverride { setting: &'static str },
+
+    #[error("no topology node named {node:?}")]
+    UnknownNode { node: String },
+
+    #[error("error rate {rate} is not between 0 and 1")]
+    InvalidErrorRate { rate: f64 },
+
+    #[error("no resource with service.name {service:?} on the telemetry client")]
+    UnknownService { service: String },
+
+    #[error("topology nodes forward to each other in a cycle: {}", .nodes.join(", "))]
+    TopologyCycle { nodes: Vec<String> },
+
+    #[error("topology node {node:?} has no {port:?} receiver port to forward to")]
+    MissingReceiverPort { node: String, port: &'static str },
+
+    #[error("{feature} is not supported by the local process backend")]
+    UnsupportedByBackend { feature: String },
+
+    #[error("the collector process is not running")]
+    ProcessNotRunning,
+
+    #[error("unknown signal {signal:?}")]
+    UnknownSignal { signal: String },
+
     #[error("no stats received from container")]
     NoContainerStats,
 
+    #[error("no memory stats in /proc/{pid}/status")]
+    NoProcessStats { pid: u32 },
+
     #[error("docker API error: {0}")]
     Docker(#[from] bollard::errors::Error),
 
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -33,7 +128,7 @@ pub enum Error {
     TestContainers(#[from] testcontainers::TestcontainersError),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Signal {
     Traces,
     Metrics,
@@ -50,4 +145,11 @@ impl std::fmt::Display for Signal {
     }
 }
 
+fn format_issues(issues: &[ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| format!("\n  - {issue}"))
+        .collect()
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,20 +1,46 @@
 use std::time::{Duration, Instant};
 
 use opentelemetry::KeyValue;
+use opentelemetry::logs::{LogRecord, Logger, LoggerProvider, Severity};
 use opentelemetry::trace::{SpanKind, TraceContextExt, Tracer};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use tokio::time::interval;
 
 use super::sdk::TelemetryClient;
+use super::traces::{TraceGenerator, TraceShape};
 use crate::error::Result;
 
+/// The histogram the generator means to be exponential. The generator cannot
+/// register it on a client that is already built, so build the client with
+/// [`TelemetryClientBuilder::exponential_histogram`](super::TelemetryClientBuilder::exponential_histogram),
+/// or it is exported with explicit buckets.
+pub const LOAD_EXPONENTIAL_HISTOGRAM: &str = "load.latency.exponential";
+const SCOPE_NAME: &str = "load-generator";
+/// Attribute values per metric instrument, so each exports several series.
+const METRIC_SERIES: usize = 10;
+const LOG_SEVERITIES: [(Severity, &str); 4] = [
+    (Severity::Debug, "DEBUG"),
+    (Severity::Info, "INFO"),
+    (Severity::Warn, "WARN"),
+    (Severity::Error, "ERROR"),
+];
+
 #[derive(Debug, Clone)]
 pub struct LoadConfig {
     pub spans_per_second: u32,
+    /// Measurements per second, spread evenly over a counter, a gauge, a
+    /// histogram and [`LOAD_EXPONENTIAL_HISTOGRAM`], which is only exported
+    /// as an exponential histogram if the client registered it as one.
     pub metrics_per_second: u32,
     pub logs_per_second: u32,
     pub duration: Duration,
     pub span_attributes_count: usize,
     pub unique_span_names: usize,
+    /// Emits whole traces of this shape instead of lone spans, as many per
+    /// second as keeps close to `spans_per_second`. The client needs a
+    /// resource for every service in the shape.
+    pub trace_shape: Option<TraceShape>,
 }
 
 impl Default for LoadConfig {
@@ -26,6 +52,7 @@ impl Default for LoadConfig {
             duration: Duration::from_secs(60),
             span_attributes_count: 10,
             unique_span_names: 100,
+            trace_shape: None,
         }
     }
 }
@@ -33,6 +60,7 @@ impl Default for LoadConfig {
 #[derive(Debug, Default)]
 pub struct LoadStats {
     pub spans_sent: usize,
+    /// Measurements recorded, not data points exported.
     pub metrics_sent: usize,
     pub logs_sent: usize,
     pub duration: Duration,
@@ -51,54 +79,155 @@ impl LoadStats {
     pub fn metrics_per_second(&self) -> f64 {
         self.per_second(self.metrics_sent)
     }
+
+    pub fn logs_per_second(&self) -> f64 {
+        self.per_second(self.logs_sent)
+    }
 }
 
+/// Sends spans, metrics and logs at the same time, each at its own rate.
 pub struct LoadGenerator<'a> {
     client: &'a TelemetryClient,
     config: LoadConfig,
 }
 
 impl<'a> LoadGenerator<'a> {
+    /// Build `client` with
+    /// `.exponential_histogram(LOAD_EXPONENTIAL_HISTOGRAM)` for the
+    /// generator's [`LOAD_EXPONENTIAL_HISTOGRAM`] to be exponential.
     pub fn new(client: &'a TelemetryClient, config: LoadConfig) -> Self {
         Self { client, config }
     }
 
     pub async fn run(&self) -> Result<LoadStats> {
+        // Built up front so an invalid shape fails before any load is sent.
+        let traces = self
+            .config
+            .trace_shape
+            .clone()
+            .map(TraceGenerator::new)
+            .transpose()?;
         let start = Instant::now();
-        let mut stats = LoadStats::default();
-
-        if self.config.spans_per_second > 0 {
-            let span_interval = Duration::from_secs_f64(1.0 / self.config.spans_per_second as f64);
-            let mut span_ticker = interval(span_interval);
-            let tracer = self.client.tracer("load-generator");
-
-            while start.elapsed() < self.config.duration {
-                span_ticker.tick().await;
-
-                let span_name = format!(
-                    "load-span-{}",
-                    stats.spans_sent % self.config.unique_span_names
-                );
-
-                let attributes: Vec<_> = (0..self.config.span_attributes_count)
-                    .map(|i| {
-                        KeyValue::new(format!("attr-{i}"), format!("value-{}", stats.spans_sent))
-                    })
-                    .collect();
-
-                let span = tracer
-                    .span_builder(span_name)
-                    .with_kind(SpanKind::Internal)
-                    .with_attributes(attributes)
-                    .start(&tracer);
-
-                opentelemetry::Context::current_with_span(span).span().end();
-                stats.spans_sent += 1;
+
+        let (spans_sent, metrics_sent, logs_sent) = tokio::join!(
+            self.run_spans(start, traces),
+            self.run_metrics(start),
+            self.run_logs(start)
+        );
+
+        self.client.flush()?;
+        Ok(LoadStats {
+            spans_sent: spans_sent?,
+            metrics_sent,
+            logs_sent,
+            duration: start.elapsed(),
+        })
+    }
+
+    async fn run_spans(&self, start: Instant, traces: Option<TraceGenerator>) -> Result<usize> {
+        if let Some(traces) = traces {
+            return self.run_traces(start, traces).await;
+        }
+        let Some(mut ticker) = ticker(self.config.spans_per_second as f64) else {
+            return Ok(0);
+        };
+        let tracer = self.client.tracer(SCOPE_NAME);
+        let mut sent = 0;
+
+        while start.elapsed() < self.config.duration {
+            ticker.tick().await;
+
+            let span_name = format!("load-span-{}", sent % self.config.unique_span_names);
+
+            let attributes: Vec<_> = (0..self.config.span_attributes_count)
+                .map(|i| KeyValue::new(format!("attr-{i}"), format!("value-{sent}")))
+                .collect();
+
+            let span = tracer
+                .span_builder(span_name)
+                .with_kind(SpanKind::Internal)
+                .with_attributes(attributes)
+                .start(&tracer);
+
+            opentelemetry::Context::current_with_span(span).span().end();
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    async fn run_traces(&self, start: Instant, mut traces: TraceGenerator) -> Result<usize> {
+        let spans_per_trace = traces.shape().span_count();
+        let traces_per_second = self.config.spans_per_second as f64 / spans_per_trace as f64;
+        let Some(mut ticker) = ticker(traces_per_second) else {
+            return Ok(0);
+        };
+        let mut sent = 0;
+
+        while start.elapsed() < self.config.duration {
+            ticker.tick().await;
+            traces.next_trace().emit(self.client)?;
+            sent += spans_per_trace;
+        }
+        Ok(sent)
+    }
+
+    async fn run_metrics(&self, start: Instant) -> usize {
+        let Some(mut ticker) = ticker(self.config.metrics_per_second as f64) else {
+            return 0;
+        };
+        let meter = self.client.meter(SCOPE_NAME);
+        let counter = meter.u64_counter("load.requests").build();
+        let gauge = meter.f64_gauge("load.queue.size").build();
+        let histogram = meter.f64_histogram("load.latency").with_unit("ms").build();
+        let exponential_histogram = meter
+            .f64_histogram(LOAD_EXPONENTIAL_HISTOGRAM)
+            .with_unit("ms")
+            .build();
+        let mut rng = StdRng::from_os_rng();
+        let mut sent = 0;
+
+        while start.elapsed() < self.config.duration {
+            ticker.tick().await;
+
+            let attributes = [KeyValue::new(
+                "series",
+                format!("series-{}", sent / 4 % METRIC_SERIES),
+            )];
+            match sent % 4 {
+                0 => counter.add(1, &attributes),
+                1 => gauge.record(rng.random_range(0.0..100.0), &attributes),
+                2 => histogram.record(rng.random_range(1.0..1000.0), &attributes),
+                _ => exponential_histogram.record(rng.random_range(1.0..1000.0), &attributes),
             }
+            sent += 1;
         }
+        sent
+    }
 
-        self.client.flush()?;
-        stats.duration = start.elapsed();
-        Ok(stats)
+    async fn run_logs(&self, start: Instant) -> usize {
+        let Some(mut ticker) = ticker(self.config.logs_per_second as f64) else {
+            return 0;
+        };
+        let logger = self.client.logger_provider().logger(SCOPE_NAME);
+        let mut sent = 0;
+
+        while start.elapsed() < self.config.duration {
+            ticker.tick().await;
+
+            let (severity, severity_text) = LOG_SEVERITIES[sent % LOG_SEVERITIES.len()];
+            let mut record = logger.create_log_record();
+            record.set_severity_number(severity);
+            record.set_severity_text(severity_text);
+            record.set_body(format!("load-log-{sent}").into());
+            record.add_attribute("sequence", sent as i64);
+            logger.emit(record);
+            sent += 1;
+        }
+        sent
     }
 }
+
+/// Ticks `per_second` times a second, or `None` for no load.
+fn ticker(per_second: f64) -> Option<tokio::time::Interval> {
+    (per_second > 0.0).then(|| interval(Duration::from_secs_f64(1.0 / per_second)))
+}
@@ -1,5 +1,12 @@
 pub mod generator;
+pub mod otlp;
 pub mod sdk;
+pub mod traces;
 
-pub use generator::{LoadConfig, LoadGenerator, LoadStats};
-pub use sdk::TelemetryClient;
+pub use generator::{LOAD_EXPONENTIAL_HISTOGRAM, LoadConfig, LoadGenerator, LoadStats};
+pub use otlp::{OtlpResponse, OtlpSender, OtlpStatus, PartialSuccess};
+pub use sdk::{
+    Compression, DEFAULT_EXPORT_TIMEOUT, DEFAULT_METRIC_INTERVAL, ExporterConfig, Processor,
+    Protocol, Resource, ResourceClient, TelemetryClient, TelemetryClientBuilder, Temporality,
+};
+pub use traces::{Latency, Operation, SyntheticSpan, SyntheticTrace, TraceGenerator, TraceShape};
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::{Buf, BufMut, Bytes};
+use opentelemetry_proto::tonic::collector::logs::v1::{
+    ExportLogsServiceRequest, ExportLogsServiceResponse,
+};
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use prost::Message;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::transport::Endpoint;
+
+use super::sdk::{DEFAULT_EXPORT_TIMEOUT, Protocol};
+use crate::error::{Error, Result, Signal};
+
+/// How the collector answered an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtlpStatus {
+    Http(u16),
+    Grpc(tonic::Code),
+}
+
+/// The `partial_success` of an export response, present when the collector
+/// rejected some items or returned a warning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialSuccess {
+    /// Rejected spans, data points or log records.
+    pub rejected: i64,
+    pub error_message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OtlpResponse {
+    pub status: OtlpStatus,
+    /// The gRPC status message, or the HTTP body of a failed request.
+    pub message: String,
+    pub partial_success: Option<PartialSuccess>,
+}
+
+impl OtlpResponse {
+    pub fn is_success(&self) -> bool {
+        match self.status {
+            OtlpStatus::Http(code) => (200..300).contains(&code),
+            OtlpStatus::Grpc(code) => code == tonic::Code::Ok,
+        }
+    }
+
+    /// Items the collector rejected, zero unless it reported a partial
+    /// success.
+    pub fn rejected(&self) -> i64 {
+        self.partial_success
+            .as_ref()
+            .map_or(0, |partial| partial.rejected)
+    }
+}
+
+/// Sends hand-built OTLP export requests without the SDK, so tests control
+/// every ID, timestamp and field, and can send payloads the SDK would never
+/// produce.
+#[derive(Debug, Clone)]
+pub struct OtlpSender {
+    protocol: Protocol,
+    endpoint: String,
+    headers: HashMap<String, String>,
+    timeout: Duration,
+    http: reqwest::Client,
+}
+
+impl OtlpSender {
+    /// `endpoint` is the receiver's base endpoint, e.g.
+    /// `http://127.0.0.1:4318`; HTTP requests go to `/v1/<signal>` under it.
+    pub fn new(protocol: Protocol, endpoint: impl Into<String>) -> Self {
+        Self {
+            protocol,
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            headers: HashMap::new(),
+            timeout: DEFAULT_EXPORT_TIMEOUT,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Sent as an HTTP header, or as gRPC metadata with the name lowercased.
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub async fn send_traces(&self, request: &ExportTraceServiceRequest) -> Result<OtlpResponse> {
+        let body = self.encode(Signal::Traces, request)?;
+        self.send_raw(Signal::Traces, body).await
+    }
+
+    pub async fn send_metrics(
+        &self,
+        request: &ExportMetricsServiceRequest,
+    ) -> Result<OtlpResponse> {
+        let body = self.encode(Signal::Metrics, request)?;
+        self.send_raw(Signal::Metrics, body).await
+    }
+
+    pub async fn send_logs(&self, request: &ExportLogsServiceRequest) -> Result<OtlpResponse> {
+        let body = self.encode(Signal::Logs, request)?;
+        self.send_raw(Signal::Logs, body).await
+    }
+
+    /// Sends `body` as is: serialized protobuf for gRPC and HTTP/protobuf,
+    /// or JSON for HTTP/JSON. Use this for payloads a typed request cannot
+    /// hold, such as strings that are not valid UTF-8.
+    pub async fn send_raw(&self, signal: Signal, body: impl Into<Bytes>) -> Result<OtlpResponse> {
+        match self.protocol {
+            Protocol::Grpc => self.send_grpc(signal, body.into()).await,
+            protocol => self.send_http(signal, protocol, body.into()).await,
+        }
+    }
+
+    fn encode<M: Message + serde::Serialize>(&self, signal: Signal, request: &M) -> Result<Bytes> {
+        match self.protocol {
+            Protocol::HttpJson => serde_json::to_vec(request)
+                .map(Bytes::from)
+                .map_err(|e| send_error(signal, e)),
+            _ => Ok(request.encode_to_vec().into()),
+        }
+    }
+
+    async fn send_grpc(&self, signal: Signal, body: Bytes) -> Result<OtlpResponse> {
+        let channel = Endpoint::from_shared(self.endpoint.clone())
+            .map_err(|e| send_error(signal, e))?
+            .timeout(self.timeout)
+            .connect_lazy();
+        let mut client = tonic::client::Grpc::new(channel);
+
+        let mut request = tonic::Request::new(body);
+        for (name, value) in &self.headers {
+            let key = MetadataKey::from_bytes(name.to_lowercase().as_bytes())
+                .map_err(|e| send_error(signal, e))?;
+            let value =
+                MetadataValue::try_from(value.as_str()).map_err(|e| send_error(signal, e))?;
+            request.metadata_mut().insert(key, value);
+        }
+
+        client.ready().await.map_err(|e| send_error(signal, e))?;
+        let path = tonic::codegen::http::uri::PathAndQuery::from_static(grpc_path(signal));
+        match client.unary(request, path, RawCodec).await {
+            Ok(response) => Ok(OtlpResponse {
+                status: OtlpStatus::Grpc(tonic::Code::Ok),
+                message: String::new(),
+                partial_success: decode_protobuf(signal, response.get_ref())?,
+            }),
+            Err(status) => Ok(OtlpResponse {
+                status: OtlpStatus::Grpc(status.code()),
+                message: status.message().to_string(),
+                partial_success: None,
+            }),
+        }
+    }
+
+    async fn send_http(
+        &self,
+        signal: Signal,
+        protocol: Protocol,
+        body: Bytes,
+    ) -> Result<OtlpResponse> {
+        let content_type = match protocol {
+            Protocol::HttpJson => "application/json",
+            _ => "application/x-protobuf",
+        };
+        let mut request = self
+            .http
+            .post(format!("{}/v1/{signal}", self.endpoint))
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.send().await.map_err(|e| send_error(signal, e))?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(|e| send_error(signal, e))?;
+        if !status.is_success() {
+            return Ok(OtlpResponse {
+                status: OtlpStatus::Http(status.as_u16()),
+                message: String::from_utf8_lossy(&body).into_owned(),
+                partial_success: None,
+            });
+        }
+
+        let partial_success = match protocol {
+            Protocol::HttpJson => decode_json(signal, &body)?,
+            _ => decode_protobuf(signal, &body)?,
+        };
+        Ok(OtlpResponse {
+            status: OtlpStatus::Http(status.as_u16()),
+            message: String::new(),
+            partial_success,
+        })
+    }
+}
+
+fn send_error(signal: Signal, error: impl std::fmt::Display) -> Error {
+    Error::OtlpSend {
+        signal,
+        message: error.to_string(),
+    }
+}
+
+fn grpc_path(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Traces => "/opentelemetry.proto.collector.trace.v1.TraceService/Export",
+        Signal::Metrics => "/opentelemetry.proto.collector.metrics.v1.MetricsService/Export",
+        Signal::Logs => "/opentelemetry.proto.collector.logs.v1.LogsService/Export",
+    }
+}
+
+/// An empty partial success means full success, so it is reported as none.
+fn partial_success(rejected: i64, error_message: String) -> Option<PartialSuccess> {
+    (rejected != 0 || !error_message.is_empty()).then_some(PartialSuccess {
+        rejected,
+        error_message,
+    })
+}
+
+fn decode_protobuf(signal: Signal, body: &[u8]) -> Result<Option<PartialSuccess>> {
+    let decoded = match signal {
+        Signal::Traces => ExportTraceServiceResponse::decode(body).map(|response| {
+            response
+                .partial_success
+                .map(|partial| (partial.rejected_spans, partial.error_message))
+        }),
+        Signal::Metrics => ExportMetricsServiceResponse::decode(body).map(|response| {
+            response
+                .partial_success
+                .map(|partial| (partial.rejected_data_points, partial.error_message))
+        }),
+        Signal::Logs => ExportLogsServiceResponse::decode(body).map(|response| {
+            response
+                .partial_success
+                .map(|partial| (partial.rejected_log_records, partial.error_message))
+        }),
+    };
+    Ok(decoded
+        .map_err(|e| send_error(signal, e))?
+        .and_then(|(rejected, error_message)| partial_success(rejected, error_message)))
+}
+
+/// Reads the partial success by hand: OTLP/JSON omits zero values and may
+/// encode the 64-bit count as a string.
+fn decode_json(signal: Signal, body: &[u8]) -> Result<Option<PartialSuccess>> {
+    if body.is_empty() {
+        return Ok(None);
+    }
+    let response: serde_json::Value =
+        serde_json::from_slice(body).map_err(|e| send_error(signal, e))?;
+    let Some(partial) = response.get("partialSuccess") else {
+        return Ok(None);
+    };
+
+    let field = match signal {
+        Signal::Traces => "rejectedSpans",
+        Signal::Metrics => "rejectedDataPoints",
+        Signal::Logs => "rejectedLogRecords",
+    };
+    let rejected = match partial.get(field) {
+        Some(serde_json::Value::String(count)) => count
+            .parse()
+            .map_err(|_| send_error(signal, format!("invalid {field}: {count:?}")))?,
+        Some(count) => count
+            .as_i64()
+            .ok_or_else(|| send_error(signal, format!("invalid {field}: {count}")))?,
+        None => 0,
+    };
+    let error_message = partial
+        .get("errorMessage")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    Ok(partial_success(rejected, error_message))
+}
+
+/// Passes gRPC message bodies through unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Bytes;
+    type Decode = Bytes;
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawCodec
+    }
+}
+
+impl Encoder for RawCodec {
+    type Item = Bytes;
+    type Error = tonic::Status;
+
+    fn encode(
+        &mut self,
+        item: Bytes,
+        dst: &mut EncodeBuf<'_>,
+    ) -> std::result::Result<(), Self::Error> {
+        dst.put(item);
+        Ok(())
+    }
+}
+
+impl Decoder for RawCodec {
+    type Item = Bytes;
+    type Error = tonic::Status;
+
+    fn decode(
+        &mut self,
+        src: &mut DecodeBuf<'_>,
+    ) -> std::result::Result<Option<Bytes>, Self::Error> {
+        Ok(Some(src.copy_to_bytes(src.remaining())))
+    }
+}
@@ -1,96 +1,462 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
 use opentelemetry::metrics::MeterProvider;
 use opentelemetry::trace::TracerProvider;
+use opentelemetry::{Key, KeyValue, Value};
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
-use opentelemetry_otlp::{LogExporter, MetricExporter, SpanExporter, WithExportConfig};
-use opentelemetry_sdk::Resource;
+use opentelemetry_otlp::{
+    LogExporter, MetricExporter, SpanExporter, WithExportConfig, WithHttpConfig, WithTonicConfig,
+};
 use opentelemetry_sdk::logs::{SdkLogger, SdkLoggerProvider};
-use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::metrics::{
+    Aggregation, Instrument, PeriodicReader, SdkMeterProvider, Stream,
+};
 use opentelemetry_sdk::trace::SdkTracerProvider;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
 
 use crate::error::{Error, Result, Signal};
 
-pub struct TelemetryClient {
-    tracer_provider: SdkTracerProvider,
-    meter_provider: SdkMeterProvider,
-    logger_provider: SdkLoggerProvider,
+pub use opentelemetry_otlp::{Compression, Protocol};
+pub use opentelemetry_sdk::Resource;
+pub use opentelemetry_sdk::metrics::Temporality;
+
+pub const DEFAULT_EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_METRIC_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_SERVICE_NAME: &str = "collector-tester";
+
+/// How spans and log records are handed to the exporter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Processor {
+    #[default]
+    Batch,
+    /// Exports each span or record as it ends, blocking the caller. gRPC
+    /// only: over HTTP the blocking client panics inside an async runtime,
+    /// so [`TelemetryClientBuilder::build`] rejects the combination.
+    Simple,
 }
 
-impl TelemetryClient {
-    pub fn new(endpoint: &str) -> Result<Self> {
-        Self::with_service_name(endpoint, "collector-tester")
+/// How one signal is exported.
+#[derive(Debug, Clone)]
+pub struct ExporterConfig {
+    protocol: Protocol,
+    endpoint: Option<String>,
+    compression: Option<Compression>,
+    headers: HashMap<String, String>,
+    timeout: Duration,
+    processor: Processor,
+}
+
+impl Default for ExporterConfig {
+    fn default() -> Self {
+        Self {
+            protocol: Protocol::Grpc,
+            endpoint: None,
+            compression: None,
+            headers: HashMap::new(),
+            timeout: DEFAULT_EXPORT_TIMEOUT,
+            processor: Processor::default(),
+        }
+    }
+}
+
+impl ExporterConfig {
+    #[must_use]
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
     }
 
-    pub fn with_service_name(endpoint: &str, service_name: &str) -> Result<Self> {
-        let resource = Resource::builder()
-            .with_service_name(service_name.to_string())
-            .build();
+    /// The full endpoint for this signal, used as is. For HTTP this
+    /// includes the path, e.g. `http://127.0.0.1:4318/v1/traces`.
+    #[must_use]
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    #[must_use]
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Sent as an HTTP header, or as gRPC metadata with the name lowercased.
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
 
-        let tracer_provider = Self::build_tracer_provider(endpoint, resource.clone())?;
-        let meter_provider = Self::build_meter_provider(endpoint, resource.clone())?;
-        let logger_provider = Self::build_logger_provider(endpoint, resource)?;
+    /// Ignored for metrics, which are exported by a periodic reader.
+    #[must_use]
+    pub fn processor(mut self, processor: Processor) -> Self {
+        self.processor = processor;
+        self
+    }
 
-        Ok(Self {
-            tracer_provider,
-            meter_provider,
-            logger_provider,
+    /// The explicit endpoint, or `base` with the signal's path appended
+    /// for HTTP.
+    fn resolve_endpoint(&self, base: Option<&str>, signal: Signal) -> Result<String> {
+        if let Some(endpoint) = &self.endpoint {
+            return Ok(endpoint.clone());
+        }
+        let base = base.ok_or_else(|| Error::ExporterBuild {
+            signal,
+            message: "no endpoint configured".to_string(),
+        })?;
+        Ok(match self.protocol {
+            Protocol::Grpc => base.to_string(),
+            _ => format!("{}/v1/{signal}", base.trim_end_matches('/')),
         })
     }
 
-    fn build_tracer_provider(endpoint: &str, resource: Resource) -> Result<SdkTracerProvider> {
-        let exporter = SpanExporter::builder()
-            .with_http()
-            .with_endpoint(endpoint)
-            .with_timeout(Duration::from_secs(10))
-            .build()
-            .map_err(|e| Error::ExporterBuild {
-                signal: Signal::Traces,
-                message: e.to_string(),
-            })?;
+    fn check_processor(&self, signal: Signal) -> Result<()> {
+        if self.processor == Processor::Simple && !matches!(self.protocol, Protocol::Grpc) {
+            return Err(Error::ExporterBuild {
+                signal,
+                message: "the simple processor requires gRPC".to_string(),
+            });
+        }
+        Ok(())
+    }
 
-        Ok(SdkTracerProvider::builder()
-            .with_resource(resource)
-            .with_batch_exporter(exporter)
-            .build())
+    fn metadata(&self, signal: Signal) -> Result<MetadataMap> {
+        let mut metadata = MetadataMap::new();
+        for (name, value) in &self.headers {
+            let key = MetadataKey::from_bytes(name.to_lowercase().as_bytes());
+            match (key, MetadataValue::try_from(value.as_str())) {
+                (Ok(key), Ok(parsed)) => {
+                    metadata.insert(key, parsed);
+                }
+                _ => {
+                    return Err(Error::ExporterBuild {
+                        signal,
+                        message: format!("invalid gRPC metadata {name}: {value}"),
+                    });
+                }
+            }
+        }
+        Ok(metadata)
     }
+}
 
-    fn build_meter_provider(endpoint: &str, resource: Resource) -> Result<SdkMeterProvider> {
-        let exporter = MetricExporter::builder()
-            .with_tonic()
-            .with_endpoint(endpoint)
-            .with_timeout(Duration::from_secs(10))
-            .build()
-            .map_err(|e| Error::ExporterBuild {
-                signal: Signal::Metrics,
-                message: e.to_string(),
-            })?;
+/// Builds an OTLP exporter of either transport from an [`ExporterConfig`].
+macro_rules! build_exporter {
+    ($builder:expr, $config:expr, $endpoint:expr, $signal:expr) => {{
+        let config = $config;
+        let signal = $signal;
+        let built = match config.protocol {
+            Protocol::Grpc => {
+                let mut builder = $builder
+                    .with_tonic()
+                    .with_endpoint($endpoint)
+                    .with_timeout(config.timeout)
+                    .with_metadata(config.metadata(signal)?);
+                if let Some(compression) = config.compression {
+                    builder = builder.with_compression(compression);
+                }
+                builder.build()
+            }
+            protocol => {
+                let mut builder = $builder
+                    .with_http()
+                    .with_protocol(protocol)
+                    .with_endpoint($endpoint)
+                    .with_timeout(config.timeout)
+                    .with_headers(config.headers.clone());
+                if let Some(compression) = config.compression {
+                    builder = builder.with_compression(compression);
+                }
+                builder.build()
+            }
+        };
+        built.map_err(|e| Error::ExporterBuild {
+            signal,
+            message: e.to_string(),
+        })
+    }};
+}
+
+/// Configures a [`TelemetryClient`]. Settings made on the builder apply to
+/// every signal; [`Self::traces`], [`Self::metrics`] and [`Self::logs`]
+/// adjust one signal on top of them.
+#[derive(Debug, Clone)]
+pub struct TelemetryClientBuilder {
+    endpoint: Option<String>,
+    service_name: String,
+    resource_attributes: Vec<KeyValue>,
+    schema_url: Option<String>,
+    resource: Option<Resource>,
+    extra_resources: Vec<Resource>,
+    traces: ExporterConfig,
+    metrics: ExporterConfig,
+    logs: ExporterConfig,
+    metric_interval: Duration,
+    temporality: Temporality,
+    exponential_histograms: Vec<String>,
+}
+
+impl Default for TelemetryClientBuilder {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            service_name: DEFAULT_SERVICE_NAME.to_string(),
+            resource_attributes: Vec::new(),
+            schema_url: None,
+            resource: None,
+            extra_resources: Vec::new(),
+            traces: ExporterConfig::default(),
+            metrics: ExporterConfig::default(),
+            logs: ExporterConfig::default(),
+            metric_interval: DEFAULT_METRIC_INTERVAL,
+            temporality: Temporality::default(),
+            exponential_histograms: Vec::new(),
+        }
+    }
+}
+
+impl TelemetryClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The collector's base endpoint, e.g. `http://127.0.0.1:4317`. HTTP
+    /// exporters append `/v1/<signal>` unless given their own endpoint.
+    #[must_use]
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    #[must_use]
+    pub fn service_name(mut self, service_name: impl Into<String>) -> Self {
+        self.service_name = service_name.into();
+        self
+    }
+
+    /// Adds an attribute to the resource alongside `service.name`, e.g.
+    /// `deployment.environment`.
+    #[must_use]
+    pub fn resource_attribute(mut self, key: impl Into<Key>, value: impl Into<Value>) -> Self {
+        self.resource_attributes.push(KeyValue::new(key, value));
+        self
+    }
+
+    #[must_use]
+    pub fn schema_url(mut self, schema_url: impl Into<String>) -> Self {
+        self.schema_url = Some(schema_url.into());
+        self
+    }
+
+    /// Uses `resource` as is, instead of one built from the service name,
+    /// resource attributes and schema URL.
+    #[must_use]
+    pub fn resource(mut self, resource: Resource) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Sends as another resource too, with its own providers and the same
+    /// exporter settings. See [`TelemetryClient::resources`].
+    #[must_use]
+    pub fn add_resource(mut self, resource: Resource) -> Self {
+        self.extra_resources.push(resource);
+        self
+    }
+
+    #[must_use]
+    pub fn protocol(self, protocol: Protocol) -> Self {
+        self.all(|config| config.protocol(protocol))
+    }
+
+    #[must_use]
+    pub fn compression(self, compression: Compression) -> Self {
+        self.all(|config| config.compression(compression))
+    }
+
+    #[must_use]
+    pub fn header(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let (name, value) = (name.into(), value.into());
+        self.all(|config| config.header(name.clone(), value.clone()))
+    }
+
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.all(|config| config.timeout(timeout))
+    }
+
+    #[must_use]
+    pub fn processor(self, processor: Processor) -> Self {
+        self.all(|config| config.processor(processor))
+    }
+
+    #[must_use]
+    pub fn traces(mut self, configure: impl FnOnce(ExporterConfig) -> ExporterConfig) -> Self {
+        self.traces = configure(self.traces);
+        self
+    }
+
+    #[must_use]
+    pub fn metrics(mut self, configure: impl FnOnce(ExporterConfig) -> ExporterConfig) -> Self {
+        self.metrics = configure(self.metrics);
+        self
+    }
+
+    #[must_use]
+    pub fn logs(mut self, configure: impl FnOnce(ExporterConfig) -> ExporterConfig) -> Self {
+        self.logs = configure(self.logs);
+        self
+    }
+
+    /// How often the periodic reader collects and exports metrics.
+    #[must_use]
+    pub fn metric_interval(mut self, interval: Duration) -> Self {
+        self.metric_interval = interval;
+        self
+    }
+
+    #[must_use]
+    pub fn temporality(mut self, temporality: Temporality) -> Self {
+        self.temporality = temporality;
+        self
+    }
+
+    /// Aggregates the histogram named `name` into base-2 exponential buckets
+    /// instead of explicit ones, e.g.
+    /// [`LOAD_EXPONENTIAL_HISTOGRAM`](super::LOAD_EXPONENTIAL_HISTOGRAM) for a
+    /// client driving a [`LoadGenerator`](super::LoadGenerator).
+    #[must_use]
+    pub fn exponential_histogram(mut self, name: impl Into<String>) -> Self {
+        self.exponential_histograms.push(name.into());
+        self
+    }
+
+    fn all(mut self, configure: impl Fn(ExporterConfig) -> ExporterConfig) -> Self {
+        self.traces = configure(self.traces);
+        self.metrics = configure(self.metrics);
+        self.logs = configure(self.logs);
+        self
+    }
+
+    pub fn build(self) -> Result<TelemetryClient> {
+        let mut resources = Vec::with_capacity(1 + self.extra_resources.len());
+        for resource in std::iter::once(self.primary_resource()).chain(self.extra_resources.clone())
+        {
+            resources.push(ResourceClient {
+                tracer_provider: self.build_tracer_provider(resource.clone())?,
+                meter_provider: self.build_meter_provider(resource.clone())?,
+                logger_provider: self.build_logger_provider(resource.clone())?,
+                resource,
+            });
+        }
+        Ok(TelemetryClient { resources })
+    }
+
+    fn primary_resource(&self) -> Resource {
+        if let Some(resource) = &self.resource {
+            return resource.clone();
+        }
+        let builder = Resource::builder().with_service_name(self.service_name.clone());
+        let attributes = self.resource_attributes.clone();
+        match &self.schema_url {
+            Some(schema_url) => builder.with_schema_url(attributes, schema_url.clone()),
+            None => builder.with_attributes(attributes),
+        }
+        .build()
+    }
+
+    fn build_tracer_provider(&self, resource: Resource) -> Result<SdkTracerProvider> {
+        let config = &self.traces;
+        config.check_processor(Signal::Traces)?;
+        let endpoint = config.resolve_endpoint(self.endpoint.as_deref(), Signal::Traces)?;
+        let exporter = build_exporter!(SpanExporter::builder(), config, endpoint, Signal::Traces)?;
+
+        let builder = SdkTracerProvider::builder().with_resource(resource);
+        Ok(match config.processor {
+            Processor::Batch => builder.with_batch_exporter(exporter),
+            Processor::Simple => builder.with_simple_exporter(exporter),
+        }
+        .build())
+    }
+
+    fn build_meter_provider(&self, resource: Resource) -> Result<SdkMeterProvider> {
+        let config = &self.metrics;
+        let endpoint = config.resolve_endpoint(self.endpoint.as_deref(), Signal::Metrics)?;
+        let exporter = build_exporter!(
+            MetricExporter::builder().with_temporality(self.temporality),
+            config,
+            endpoint,
+            Signal::Metrics
+        )?;
 
         let reader = PeriodicReader::builder(exporter)
-            .with_interval(Duration::from_secs(1))
+            .with_interval(self.metric_interval)
             .build();
 
+        let exponential_histograms = self.exponential_histograms.clone();
+        let exponential_view = move |instrument: &Instrument| {
+            exponential_histograms
+                .iter()
+                .any(|name| name == instrument.name())
+                .then(|| {
+                    Stream::builder()
+                        .with_aggregation(Aggregation::Base2ExponentialHistogram {
+                            max_size: 160,
+                            max_scale: 20,
+                            record_min_max: true,
+                        })
+                        .build()
+                        .ok()
+                })
+                .flatten()
+        };
+
         Ok(SdkMeterProvider::builder()
             .with_resource(resource)
             .with_reader(reader)
+            .with_view(exponential_view)
             .build())
     }
 
-    fn build_logger_provider(endpoint: &str, resource: Resource) -> Result<SdkLoggerProvider> {
-        let exporter = LogExporter::builder()
-            .with_tonic()
-            .with_endpoint(endpoint)
-            .with_timeout(Duration::from_secs(10))
-            .build()
-            .map_err(|e| Error::ExporterBuild {
-                signal: Signal::Logs,
-                message: e.to_string(),
-            })?;
+    fn build_logger_provider(&self, resource: Resource) -> Result<SdkLoggerProvider> {
+        let config = &self.logs;
+        config.check_processor(Signal::Logs)?;
+        let endpoint = config.resolve_endpoint(self.endpoint.as_deref(), Signal::Logs)?;
+        let exporter = build_exporter!(LogExporter::builder(), config, endpoint, Signal::Logs)?;
 
-        Ok(SdkLoggerProvider::builder()
-            .with_resource(resource)
-            .with_batch_exporter(exporter)
-            .build())
+        let builder = SdkLoggerProvider::builder().with_resource(resource);
+        Ok(match config.processor {
+            Processor::Batch => builder.with_batch_exporter(exporter),
+            Processor::Simple => builder.with_simple_exporter(exporter),
+        }
+        .build())
+    }
+}
+
+/// The providers for one resource of a [`TelemetryClient`].
+pub struct ResourceClient {
+    resource: Resource,
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+    logger_provider: SdkLoggerProvider,
+}
+
+impl ResourceClient {
+    pub fn resource(&self) -> &Resource {
+        &self.resource
+    }
+
+    pub fn service_name(&self) -> Option<String> {
+        self.resource
+            .get(&Key::from_static_str("service.name"))
+            .map(|value| value.as_str().into_owned())
     }
 
     pub fn tracer(&self, name: &'static str) -> opentelemetry_sdk::trace::Tracer {
@@ -105,16 +471,13 @@ impl TelemetryClient {
         OpenTelemetryTracingBridge::new(&self.logger_provider)
     }
 
-    #[must_use = "flush result should be handled"]
-    pub fn flush(&self) -> Result<()> {
-        self.flush_traces()?;
-        self.flush_metrics()?;
-        self.flush_logs()?;
-        Ok(())
+    /// The provider behind [`Self::logger`], for emitting log records
+    /// directly through the logs API.
+    pub fn logger_provider(&self) -> &SdkLoggerProvider {
+        &self.logger_provider
     }
 
-    #[must_use = "flush result should be handled"]
-    pub fn flush_traces(&self) -> Result<()> {
+    fn flush_traces(&self) -> Result<()> {
         self.tracer_provider
             .force_flush()
             .map_err(|e| Error::Flush {
@@ -123,16 +486,14 @@ impl TelemetryClient {
             })
     }
 
-    #[must_use = "flush result should be handled"]
-    pub fn flush_metrics(&self) -> Result<()> {
+    fn flush_metrics(&self) -> Result<()> {
         self.meter_provider.force_flush().map_err(|e| Error::Flush {
             signal: Signal::Metrics,
             message: e.to_string(),
         })
     }
 
-    #[must_use = "flush result should be handled"]
-    pub fn flush_logs(&self) -> Result<()> {
+    fn flush_logs(&self) -> Result<()> {
         self.logger_provider
             .force_flush()
             .map_err(|e| Error::Flush {
@@ -141,7 +502,7 @@ impl TelemetryClient {
             })
     }
 
-    pub fn shutdown(self) -> Result<()> {
+    fn shutdown(&self) -> Result<()> {
         self.tracer_provider
             .shutdown()
             .map_err(|e| Error::Shutdown {
@@ -159,7 +520,92 @@ impl TelemetryClient {
             .map_err(|e| Error::Shutdown {
                 signal: Signal::Logs,
                 message: e.to_string(),
-            })?;
+            })
+    }
+}
+
+/// Sends telemetry through the OpenTelemetry SDK. `tracer`, `meter` and
+/// `logger` use the first resource; [`Self::resources`] reaches the others.
+pub struct TelemetryClient {
+    resources: Vec<ResourceClient>,
+}
+
+impl TelemetryClient {
+    pub fn builder() -> TelemetryClientBuilder {
+        TelemetryClientBuilder::new()
+    }
+
+    /// Sends traces over HTTP to `endpoint`, which must include the
+    /// `/v1/traces` path, and metrics and logs over gRPC to the same
+    /// endpoint.
+    pub fn new(endpoint: &str) -> Result<Self> {
+        Self::with_service_name(endpoint, DEFAULT_SERVICE_NAME)
+    }
+
+    pub fn with_service_name(endpoint: &str, service_name: &str) -> Result<Self> {
+        Self::builder()
+            .endpoint(endpoint)
+            .service_name(service_name)
+            .traces(|traces| traces.protocol(Protocol::HttpBinary).endpoint(endpoint))
+            .build()
+    }
+
+    /// Every resource, in the order they were configured, starting with the
+    /// builder's own.
+    pub fn resources(&self) -> &[ResourceClient] {
+        &self.resources
+    }
+
+    fn primary(&self) -> &ResourceClient {
+        &self.resources[0]
+    }
+
+    pub fn tracer(&self, name: &'static str) -> opentelemetry_sdk::trace::Tracer {
+        self.primary().tracer(name)
+    }
+
+    pub fn meter(&self, name: &'static str) -> opentelemetry::metrics::Meter {
+        self.primary().meter(name)
+    }
+
+    pub fn logger(&self) -> OpenTelemetryTracingBridge<SdkLoggerProvider, SdkLogger> {
+        self.primary().logger()
+    }
+
+    pub fn logger_provider(&self) -> &SdkLoggerProvider {
+        self.primary().logger_provider()
+    }
+
+    #[must_use = "flush result should be handled"]
+    pub fn flush(&self) -> Result<()> {
+        self.flush_traces()?;
+        self.flush_metrics()?;
+        self.flush_logs()?;
         Ok(())
     }
+
+    #[must_use = "flush result should be handled"]
+    pub fn flush_traces(&self) -> Result<()> {
+        self.resources
+            .iter()
+            .try_for_each(ResourceClient::flush_traces)
+    }
+
+    #[must_use = "flush result should be handled"]
+    pub fn flush_metrics(&self) -> Result<()> {
+        self.resources
+            .iter()
+            .try_for_each(ResourceClient::flush_metrics)
+    }
+
+    #[must_use = "flush result should be handled"]
+    pub fn flush_logs(&self) -> Result<()> {
+        self.resources
+            .iter()
+            .try_for_each(ResourceClient::flush_logs)
+    }
+
+    pub fn shutdown(self) -> Result<()> {
+        self.resources.iter().try_for_each(ResourceClient::shutdown)
+    }
 }
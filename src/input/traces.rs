@@ -0,0 +1,496 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use opentelemetry::KeyValue;
+use opentelemetry::trace::{
+    SpanContext, SpanId, SpanKind, Status, TraceContextExt, TraceFlags, TraceId, TraceState, Tracer,
+};
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::common::v1::{self as common, AnyValue, InstrumentationScope};
+use opentelemetry_proto::tonic::resource::v1::Resource as ProtoResource;
+use opentelemetry_proto::tonic::trace::v1::{self as proto, ResourceSpans, ScopeSpans};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::otlp::{OtlpResponse, OtlpSender};
+use super::sdk::TelemetryClient;
+use crate::error::{Error, Result};
+
+const SCOPE_NAME: &str = "collector-tester";
+/// Time between a client span starting and its server span starting, and
+/// between the server span ending and the client span ending.
+const NETWORK_DELAY: Duration = Duration::from_micros(500);
+
+/// How long an operation spends in its own code, excluding the calls it
+/// makes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Latency {
+    Fixed(Duration),
+    Uniform {
+        min: Duration,
+        max: Duration,
+    },
+    /// Right-skewed like most real latencies: half the samples fall below
+    /// `median`, and `sigma` widens the tail.
+    LogNormal {
+        median: Duration,
+        sigma: f64,
+    },
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self::LogNormal {
+            median: Duration::from_millis(10),
+            sigma: 0.5,
+        }
+    }
+}
+
+impl Latency {
+    fn sample(&self, rng: &mut StdRng) -> Duration {
+        match *self {
+            Self::Fixed(duration) => duration,
+            Self::Uniform { min, max } if max > min => {
+                Duration::from_secs_f64(rng.random_range(min.as_secs_f64()..max.as_secs_f64()))
+            }
+            Self::Uniform { min, .. } => min,
+            Self::LogNormal { median, sigma } => {
+                // Box-Muller; 1 - u keeps the logarithm finite.
+                let u1 = 1.0 - rng.random::<f64>();
+                let u2 = rng.random::<f64>();
+                let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+                median.mul_f64((sigma * z).exp())
+            }
+        }
+    }
+}
+
+/// An operation in one service and the operations it calls, in order.
+///
+/// A call to another service becomes a `Client` span in the caller with a
+/// `Server` span in the callee under it; a call within the same service
+/// becomes an `Internal` span.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    service: String,
+    name: String,
+    calls: Vec<Operation>,
+    latency: Option<Latency>,
+    error_rate: Option<f64>,
+}
+
+impl Operation {
+    pub fn new(service: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            name: name.into(),
+            calls: Vec::new(),
+            latency: None,
+            error_rate: None,
+        }
+    }
+
+    #[must_use]
+    pub fn call(mut self, operation: Operation) -> Self {
+        self.calls.push(operation);
+        self
+    }
+
+    /// Overrides the shape's latency for this operation only.
+    #[must_use]
+    pub fn latency(mut self, latency: Latency) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Overrides the shape's error rate for this operation only. Must be
+    /// from 0 to 1.
+    #[must_use]
+    pub fn error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = Some(rate);
+        self
+    }
+
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Operation, Option<&'a Operation>)) {
+        self.visit_from(None, f);
+    }
+
+    fn visit_from<'a>(
+        &'a self,
+        caller: Option<&'a Operation>,
+        f: &mut impl FnMut(&'a Operation, Option<&'a Operation>),
+    ) {
+        f(self, caller);
+        for call in &self.calls {
+            call.visit_from(Some(self), f);
+        }
+    }
+}
+
+/// The call graph every generated trace follows, starting from the `Server`
+/// span of its root operation.
+#[derive(Debug, Clone)]
+pub struct TraceShape {
+    root: Operation,
+    latency: Latency,
+    error_rate: f64,
+}
+
+impl TraceShape {
+    pub fn new(root: Operation) -> Self {
+        Self {
+            root,
+            latency: Latency::default(),
+            error_rate: 0.0,
+        }
+    }
+
+    #[must_use]
+    pub fn latency(mut self, latency: Latency) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// The chance, from 0 to 1, that an operation fails. A failed operation
+    /// sets an error status on its span and on the client span calling it.
+    #[must_use]
+    pub fn error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate;
+        self
+    }
+
+    /// Every service in the call graph, in the order first called. A
+    /// [`TelemetryClient`] emitting these traces needs a resource for each.
+    pub fn services(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut services = Vec::new();
+        self.root.visit(&mut |operation, _| {
+            if seen.insert(operation.service.as_str()) {
+                services.push(operation.service.clone());
+            }
+        });
+        services
+    }
+
+    /// Checks that every error rate is a probability.
+    pub(crate) fn validate(&self) -> Result<()> {
+        let mut rates = vec![self.error_rate];
+        self.root
+            .visit(&mut |operation, _| rates.extend(operation.error_rate));
+        match rates.into_iter().find(|rate| !(0.0..=1.0).contains(rate)) {
+            Some(rate) => Err(Error::InvalidErrorRate { rate }),
+            None => Ok(()),
+        }
+    }
+
+    /// The number of spans in each trace.
+    pub fn span_count(&self) -> usize {
+        let mut count = 0;
+        self.root.visit(&mut |operation, caller| {
+            count += match caller {
+                Some(caller) if caller.service != operation.service => 2,
+                _ => 1,
+            };
+        });
+        count
+    }
+}
+
+/// Generates traces of one shape. Seed it for the same IDs, latencies and
+/// errors on every run.
+#[derive(Debug, Clone)]
+pub struct TraceGenerator {
+    shape: TraceShape,
+    rng: StdRng,
+}
+
+impl TraceGenerator {
+    /// Fails with [`Error::InvalidErrorRate`] if an error rate in `shape` is
+    /// not from 0 to 1.
+    pub fn new(shape: TraceShape) -> Result<Self> {
+        shape.validate()?;
+        Ok(Self {
+            shape,
+            rng: StdRng::from_os_rng(),
+        })
+    }
+
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn shape(&self) -> &TraceShape {
+        &self.shape
+    }
+
+    /// A trace whose root span starts now.
+    pub fn next_trace(&mut self) -> SyntheticTrace {
+        self.next_trace_at(SystemTime::now())
+    }
+
+    pub fn next_trace_at(&mut self, start: SystemTime) -> SyntheticTrace {
+        let trace_id = TraceId::from_bytes(self.rng.random_range(1..=u128::MAX).to_be_bytes());
+        let mut spans = Vec::with_capacity(self.shape.span_count());
+        let root = self.shape.root.clone();
+        self.plan(&root, None, None, start, &mut spans);
+        SyntheticTrace { trace_id, spans }
+    }
+
+    /// Adds the spans for `operation` called by `caller` from the span at
+    /// `parent`, returning when the last of them ends.
+    fn plan(
+        &mut self,
+        operation: &Operation,
+        caller: Option<&Operation>,
+        parent: Option<usize>,
+        start: SystemTime,
+        spans: &mut Vec<SyntheticSpan>,
+    ) -> SystemTime {
+        let error_rate = operation.error_rate.unwrap_or(self.shape.error_rate);
+        let error = self.rng.random_bool(error_rate);
+
+        let (kind, parent, start, client) = match caller {
+            None => (SpanKind::Server, None, start, None),
+            Some(caller) if caller.service == operation.service => {
+                (SpanKind::Internal, parent, start, None)
+            }
+            Some(caller) => {
+                let client = spans.len();
+                spans.push(self.span(caller, &operation.name, SpanKind::Client, parent, start));
+                spans[client]
+                    .attributes
+                    .push(KeyValue::new("peer.service", operation.service.clone()));
+                spans[client].error = error;
+                (
+                    SpanKind::Server,
+                    Some(client),
+                    start + NETWORK_DELAY,
+                    Some(client),
+                )
+            }
+        };
+
+        let index = spans.len();
+        spans.push(self.span(operation, &operation.name, kind, parent, start));
+        spans[index].error = error;
+
+        let own_time = operation
+            .latency
+            .unwrap_or(self.shape.latency)
+            .sample(&mut self.rng);
+        let mut cursor = start + own_time / 2;
+        for call in &operation.calls {
+            cursor = self.plan(call, Some(operation), Some(index), cursor, spans);
+        }
+        let end = cursor + own_time / 2;
+        spans[index].end = end;
+
+        match client {
+            Some(client) => {
+                spans[client].end = end + NETWORK_DELAY;
+                spans[client].end
+            }
+            None => end,
+        }
+    }
+
+    fn span(
+        &mut self,
+        operation: &Operation,
+        name: &str,
+        kind: SpanKind,
+        parent: Option<usize>,
+        start: SystemTime,
+    ) -> SyntheticSpan {
+        SyntheticSpan {
+            service: operation.service.clone(),
+            name: name.to_string(),
+            kind,
+            span_id: SpanId::from_bytes(self.rng.random_range(1..=u64::MAX).to_be_bytes()),
+            parent,
+            start,
+            end: start,
+            error: false,
+            attributes: Vec::new(),
+        }
+    }
+}
+
+/// One span of a [`SyntheticTrace`].
+#[derive(Debug, Clone)]
+pub struct SyntheticSpan {
+    pub service: String,
+    pub name: String,
+    pub kind: SpanKind,
+    pub span_id: SpanId,
+    /// The index of the parent span within the trace.
+    pub parent: Option<usize>,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub error: bool,
+    pub attributes: Vec<KeyValue>,
+}
+
+/// A complete trace, with parents before their children.
+#[derive(Debug, Clone)]
+pub struct SyntheticTrace {
+    pub trace_id: TraceId,
+    pub spans: Vec<SyntheticSpan>,
+}
+
+impl SyntheticTrace {
+    /// Emits every span through the resource of `client` whose
+    /// `service.name` matches the span's service, keeping the generated IDs
+    /// and timestamps. Spans are exported by the client's processors, so
+    /// flush the client to send them. Nothing is emitted if the client lacks
+    /// a resource for any service.
+    pub fn emit(&self, client: &TelemetryClient) -> Result<()> {
+        // Find every tracer first so a missing service emits nothing.
+        let tracers = self
+            .spans
+            .iter()
+            .map(|span| {
+                client
+                    .resources()
+                    .iter()
+                    .find(|resource| resource.service_name().as_deref() == Some(&span.service))
+                    .map(|resource| resource.tracer(SCOPE_NAME))
+                    .ok_or_else(|| Error::UnknownService {
+                        service: span.service.clone(),
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (span, tracer) in self.spans.iter().zip(tracers) {
+            let parent_cx = match span.parent.map(|parent| &self.spans[parent]) {
+                Some(parent) => {
+                    opentelemetry::Context::new().with_remote_span_context(SpanContext::new(
+                        self.trace_id,
+                        parent.span_id,
+                        TraceFlags::SAMPLED,
+                        parent.service != span.service,
+                        TraceState::default(),
+                    ))
+                }
+                None => opentelemetry::Context::new(),
+            };
+
+            let mut builder = tracer
+                .span_builder(span.name.clone())
+                .with_kind(span.kind.clone())
+                .with_trace_id(self.trace_id)
+                .with_span_id(span.span_id)
+                .with_start_time(span.start)
+                .with_attributes(span.attributes.clone());
+            if span.error {
+                builder = builder.with_status(Status::error("synthetic error"));
+            }
+            let otel_span = builder.start_with_context(&tracer, &parent_cx);
+            opentelemetry::Context::current_with_span(otel_span)
+                .span()
+                .end_with_timestamp(span.end);
+        }
+        Ok(())
+    }
+
+    /// The trace as one export request, with a resource per service.
+    pub fn to_request(&self) -> ExportTraceServiceRequest {
+        let mut resource_spans: Vec<ResourceSpans> = Vec::new();
+        for span in &self.spans {
+            let proto_span = self.to_proto(span);
+            let existing = resource_spans.iter_mut().find(|resource_spans| {
+                resource_spans
+                    .resource
+                    .as_ref()
+                    .is_some_and(|resource| service_name(resource) == Some(&span.service))
+            });
+            match existing {
+                Some(existing) => existing.scope_spans[0].spans.push(proto_span),
+                None => resource_spans.push(ResourceSpans {
+                    resource: Some(ProtoResource {
+                        attributes: vec![string_attribute("service.name", &span.service)],
+                        ..Default::default()
+                    }),
+                    scope_spans: vec![ScopeSpans {
+                        scope: Some(InstrumentationScope {
+                            name: SCOPE_NAME.to_string(),
+                            ..Default::default()
+                        }),
+                        spans: vec![proto_span],
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }),
+            }
+        }
+        ExportTraceServiceRequest { resource_spans }
+    }
+
+    pub async fn send(&self, sender: &OtlpSender) -> Result<OtlpResponse> {
+        sender.send_traces(&self.to_request()).await
+    }
+
+    fn to_proto(&self, span: &SyntheticSpan) -> proto::Span {
+        let kind = match span.kind {
+            SpanKind::Client => proto::span::SpanKind::Client,
+            SpanKind::Server => proto::span::SpanKind::Server,
+            SpanKind::Producer => proto::span::SpanKind::Producer,
+            SpanKind::Consumer => proto::span::SpanKind::Consumer,
+            SpanKind::Internal => proto::span::SpanKind::Internal,
+        };
+        let status = span.error.then(|| proto::Status {
+            code: proto::status::StatusCode::Error as i32,
+            message: "synthetic error".to_string(),
+        });
+        proto::Span {
+            trace_id: self.trace_id.to_bytes().to_vec(),
+            span_id: span.span_id.to_bytes().to_vec(),
+            parent_span_id: span
+                .parent
+                .map(|parent| self.spans[parent].span_id.to_bytes().to_vec())
+                .unwrap_or_default(),
+            name: span.name.clone(),
+            kind: kind as i32,
+            start_time_unix_nano: unix_nanos(span.start),
+            end_time_unix_nano: unix_nanos(span.end),
+            attributes: span
+                .attributes
+                .iter()
+                .map(|attribute| {
+                    string_attribute(attribute.key.as_str(), &attribute.value.as_str())
+                })
+                .collect(),
+            status,
+            ..Default::default()
+        }
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64)
+}
+
+fn string_attribute(key: &str, value: &str) -> common::KeyValue {
+    common::KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(common::any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+fn service_name(resource: &ProtoResource) -> Option<&String> {
+    resource
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key == "service.name")
+        .and_then(|attribute| attribute.value.as_ref()?.value.as_ref())
+        .and_then(|value| match value {
+            common::any_value::Value::StringValue(name) => Some(name),
+            _ => None,
+        })
+}
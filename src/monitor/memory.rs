@@ -75,47 +75,84 @@ impl ContainerMonitor {
     }
 
     pub fn analyse(&self) -> MemoryAnalysis {
-        if self.samples.is_empty() {
-            return MemoryAnalysis::default();
-        }
+        MemoryAnalysis::from_samples(&self.samples)
+    }
+}
 
-        let (min, max, sum, count) = self.samples.iter().fold(
-            (u64::MAX, 0u64, 0u64, 0usize),
-            |(min, max, sum, count), sample| {
-                (
-                    min.min(sample.usage_bytes),
-                    max.max(sample.usage_bytes),
-                    sum + sample.usage_bytes,
-                    count + 1,
-                )
-            },
-        );
+/// Samples the resident memory of a collector running as a local process,
+/// the equivalent of [`ContainerMonitor`] for the local binary backend.
+pub struct ProcessMonitor {
+    pid: u32,
+    samples: Vec<MemorySnapshot>,
+}
 
-        MemoryAnalysis {
-            min_bytes: min,
-            max_bytes: max,
-            avg_bytes: sum / count as u64,
-            sample_count: count,
-            growth_rate_bytes_per_sec: self.calculate_growth_rate(),
+impl ProcessMonitor {
+    pub fn new(pid: u32) -> Self {
+        Self {
+            pid,
+            samples: Vec::new(),
         }
     }
 
-    fn calculate_growth_rate(&self) -> f64 {
-        match self.samples.as_slice() {
-            [] | [_] => 0.0,
-            samples => {
-                let first = &samples[0];
-                let last = &samples[samples.len() - 1];
-                let duration = last.timestamp.duration_since(first.timestamp);
+    /// Samples `pid` from now on, e.g. after the collector was restarted.
+    pub fn set_pid(&mut self, pid: u32) {
+        self.pid = pid;
+    }
 
-                if duration.is_zero() {
-                    0.0
-                } else {
-                    (last.usage_bytes as f64 - first.usage_bytes as f64) / duration.as_secs_f64()
-                }
-            }
+    /// Reads `VmRSS` and `VmHWM` from `/proc/<pid>/status`. There is no
+    /// limit to report, so `limit_bytes` is always `None`.
+    pub async fn sample(&mut self) -> Result<MemorySnapshot> {
+        let status = tokio::fs::read_to_string(format!("/proc/{}/status", self.pid)).await?;
+        let (Some(usage_bytes), Some(max_usage_bytes)) = (
+            status_bytes(&status, "VmRSS"),
+            status_bytes(&status, "VmHWM"),
+        ) else {
+            return Err(Error::NoProcessStats { pid: self.pid });
+        };
+
+        let snapshot = MemorySnapshot {
+            timestamp: Instant::now(),
+            usage_bytes,
+            max_usage_bytes,
+            limit_bytes: None,
+        };
+        self.samples.push(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    pub async fn monitor_continuous(
+        &mut self,
+        duration: Duration,
+        sample_interval: Duration,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let mut ticker = interval(sample_interval);
+
+        while start.elapsed() < duration {
+            ticker.tick().await;
+            self.sample().await?;
         }
+
+        Ok(())
     }
+
+    pub fn analyse(&self) -> MemoryAnalysis {
+        MemoryAnalysis::from_samples(&self.samples)
+    }
+}
+
+/// Parses a line such as `VmRSS:     1234 kB` from `/proc/<pid>/status`.
+fn status_bytes(status: &str, field: &str) -> Option<u64> {
+    status.lines().find_map(|line| {
+        let value = line.strip_prefix(field)?.strip_prefix(':')?;
+        let kilobytes = value
+            .trim()
+            .strip_suffix("kB")?
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        Some(kilobytes * 1024)
+    })
 }
 
 #[derive(Debug, Default)]
@@ -130,6 +167,32 @@ pub struct MemoryAnalysis {
 impl MemoryAnalysis {
     const BYTES_PER_MB: f64 = 1_000_000.0;
 
+    pub fn from_samples(samples: &[MemorySnapshot]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let (min, max, sum, count) = samples.iter().fold(
+            (u64::MAX, 0u64, 0u64, 0usize),
+            |(min, max, sum, count), sample| {
+                (
+                    min.min(sample.usage_bytes),
+                    max.max(sample.usage_bytes),
+                    sum + sample.usage_bytes,
+                    count + 1,
+                )
+            },
+        );
+
+        Self {
+            min_bytes: min,
+            max_bytes: max,
+            avg_bytes: sum / count as u64,
+            sample_count: count,
+            growth_rate_bytes_per_sec: growth_rate(samples),
+        }
+    }
+
     pub fn min_mb(&self) -> f64 {
         self.min_bytes as f64 / Self::BYTES_PER_MB
     }
@@ -159,3 +222,20 @@ impl MemoryAnalysis {
         self.max_bytes as f64 + projected_growth > limit_bytes as f64
     }
 }
+
+fn growth_rate(samples: &[MemorySnapshot]) -> f64 {
+    match samples {
+        [] | [_] => 0.0,
+        samples => {
+            let first = &samples[0];
+            let last = &samples[samples.len() - 1];
+            let duration = last.timestamp.duration_since(first.timestamp);
+
+            if duration.is_zero() {
+                0.0
+            } else {
+                (last.usage_bytes as f64 - first.usage_bytes as f64) / duration.as_secs_f64()
+            }
+        }
+    }
+}
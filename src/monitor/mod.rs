@@ -1,22 +1,74 @@
 pub mod memory;
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use tokio::time::interval;
 
 use crate::container::CollectorTestHarness;
 use crate::error::Result;
-use crate::input::{LoadConfig, LoadGenerator, LoadStats, TelemetryClient};
+use crate::input::{
+    LOAD_EXPONENTIAL_HISTOGRAM, LoadConfig, LoadGenerator, LoadStats, Protocol, TelemetryClient,
+};
+
+pub use memory::{ContainerMonitor, MemoryAnalysis, MemorySnapshot, ProcessMonitor};
 
-pub use memory::{ContainerMonitor, MemoryAnalysis, MemorySnapshot};
+const TRACES_PATH: &str = "/v1/traces";
 
 pub struct LoadTestHarness {
     harness: CollectorTestHarness,
-    monitor: ContainerMonitor,
+    monitor: Monitor,
     telemetry_endpoint: String,
 }
 
+enum Monitor {
+    Container(ContainerMonitor),
+    Process(ProcessMonitor),
+}
+
+impl Monitor {
+    async fn monitor_continuous(
+        &mut self,
+        harness: &CollectorTestHarness,
+        duration: Duration,
+        sample_interval: Duration,
+    ) -> Result<()> {
+        match self {
+            Monitor::Container(monitor) => {
+                monitor.monitor_continuous(duration, sample_interval).await
+            }
+            Monitor::Process(monitor) => {
+                let start = Instant::now();
+                let mut ticker = interval(sample_interval);
+                while start.elapsed() < duration {
+                    ticker.tick().await;
+                    // A restart gives the collector a new pid.
+                    if let Some(pid) = harness.backend().pid() {
+                        monitor.set_pid(pid);
+                    }
+                    monitor.sample().await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn analyse(&self) -> MemoryAnalysis {
+        match self {
+            Monitor::Container(monitor) => monitor.analyse(),
+            Monitor::Process(monitor) => monitor.analyse(),
+        }
+    }
+}
+
 impl LoadTestHarness {
+    /// `telemetry_endpoint` is the collector's OTLP/HTTP traces endpoint,
+    /// e.g. `http://127.0.0.1:4318/v1/traces`. Metrics and logs are sent
+    /// over HTTP too, to the `/v1/metrics` and `/v1/logs` paths beside it.
     pub async fn new(harness: CollectorTestHarness, telemetry_endpoint: String) -> Result<Self> {
-        let monitor = ContainerMonitor::new(harness.container_id()).await?;
+        let monitor = match harness.backend().pid() {
+            Some(pid) => Monitor::Process(ProcessMonitor::new(pid)),
+            None => Monitor::Container(ContainerMonitor::new(harness.container_id()).await?),
+        };
 
         Ok(Self {
             harness,
@@ -30,14 +82,23 @@ impl LoadTestHarness {
         load_config: LoadConfig,
         monitor_interval: Duration,
     ) -> Result<LoadTestResult> {
-        let client = TelemetryClient::new(&self.telemetry_endpoint)?;
+        let base_endpoint = self
+            .telemetry_endpoint
+            .strip_suffix(TRACES_PATH)
+            .unwrap_or(&self.telemetry_endpoint);
+        let client = TelemetryClient::builder()
+            .endpoint(base_endpoint)
+            .protocol(Protocol::HttpBinary)
+            .traces(|traces| traces.endpoint(&self.telemetry_endpoint))
+            .exponential_histogram(LOAD_EXPONENTIAL_HISTOGRAM)
+            .build()?;
         let monitor_duration = load_config.duration;
         let generator = LoadGenerator::new(&client, load_config);
 
         let (load_result, monitor_result) = tokio::join!(
             generator.run(),
             self.monitor
-                .monitor_continuous(monitor_duration, monitor_interval)
+                .monitor_continuous(&self.harness, monitor_duration, monitor_interval)
         );
 
         let load_stats = load_result?;
@@ -52,6 +113,16 @@ impl LoadTestHarness {
         })
     }
 
+    pub fn harness(&self) -> &CollectorTestHarness {
+        &self.harness
+    }
+
+    /// Mutable access for lifecycle operations such as
+    /// [`CollectorTestHarness::restart`] between load tests.
+    pub fn harness_mut(&mut self) -> &mut CollectorTestHarness {
+        &mut self.harness
+    }
+
     pub async fn shutdown(self) -> Result<()> {
         self.harness.shutdown().await
     }
@@ -79,6 +150,7 @@ impl LoadTestResult {
             "Load Test Results:\n\
              - Spans sent: {} ({:.2}/s)\n\
              - Metrics sent: {} ({:.2}/s)\n\
+             - Logs sent: {} ({:.2}/s)\n\
              - Duration: {:?}\n\
              - Memory min: {:.2} MB\n\
              - Memory max: {:.2} MB\n\
@@ -88,6 +160,8 @@ impl LoadTestResult {
             self.load_stats.spans_per_second(),
             self.load_stats.metrics_sent,
             self.load_stats.metrics_per_second(),
+            self.load_stats.logs_sent,
+            self.load_stats.logs_per_second(),
             self.load_stats.duration,
             self.memory_analysis.min_mb(),
             self.memory_analysis.max_mb(),
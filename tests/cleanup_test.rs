@@ -0,0 +1,128 @@
+mod common;
+
+use std::time::Duration;
+
+use bollard::Docker;
+use bollard::query_parameters::InspectContainerOptions;
+use collector_tester::container::{
+    LABEL_HOST, LABEL_MANAGED, LABEL_PID, LABEL_RUN_ID, LABEL_TEST, host_id, reap_stale_containers,
+    reap_stale_containers_older_than, run_id,
+};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{GenericImage, ImageExt};
+
+async fn container_exists(docker: &Docker, id: &str) -> bool {
+    docker
+        .inspect_container(id, None::<InspectContainerOptions>)
+        .await
+        .is_ok()
+}
+
+#[test]
+fn test_run_id_is_stable_within_process() {
+    assert_eq!(run_id(), run_id());
+    assert!(run_id().starts_with(&format!("{}-", std::process::id())));
+}
+
+#[tokio::test]
+async fn test_containers_are_labelled() {
+    let harness = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    let docker = Docker::connect_with_local_defaults().expect("failed to connect to docker");
+    let labels = docker
+        .inspect_container(harness.container_id(), None::<InspectContainerOptions>)
+        .await
+        .expect("failed to inspect container")
+        .config
+        .and_then(|config| config.labels)
+        .expect("container has no labels");
+
+    assert_eq!(labels[LABEL_MANAGED], "true");
+    assert_eq!(labels[LABEL_RUN_ID], run_id());
+    assert_eq!(labels[LABEL_PID], std::process::id().to_string());
+    assert_eq!(labels[LABEL_HOST], host_id());
+    assert!(labels[LABEL_TEST].contains("test_containers_are_labelled"));
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_container_removed_when_test_panics() {
+    let (id_tx, id_rx) = tokio::sync::oneshot::channel();
+
+    let result = tokio::spawn(async move {
+        let harness = common::harness("basic.yaml")
+            .start()
+            .await
+            .expect("failed to start harness");
+        id_tx.send(harness.container_id().to_string()).unwrap();
+        panic!("deliberate failure");
+    })
+    .await;
+    assert!(result.expect_err("task should panic").is_panic());
+
+    let id = id_rx.await.expect("harness did not start");
+    let docker = Docker::connect_with_local_defaults().expect("failed to connect to docker");
+    assert!(!container_exists(&docker, &id).await);
+}
+
+#[test]
+fn test_host_id_is_stable_within_process() {
+    assert_eq!(host_id(), host_id());
+    assert!(!host_id().is_empty());
+}
+
+#[tokio::test]
+async fn test_reap_removes_containers_of_exited_processes() {
+    let stale = GenericImage::new("otel/opentelemetry-collector-contrib", "latest")
+        .with_labels([
+            (LABEL_MANAGED, "true"),
+            (LABEL_RUN_ID, "exited-run"),
+            (LABEL_PID, "4294967295"),
+            (LABEL_HOST, host_id()),
+        ])
+        .start()
+        .await
+        .expect("failed to start stale container");
+    let foreign = GenericImage::new("otel/opentelemetry-collector-contrib", "latest")
+        .with_labels([
+            (LABEL_MANAGED, "true"),
+            (LABEL_RUN_ID, "other-host-run"),
+            (LABEL_PID, "4294967295"),
+            (LABEL_HOST, "other-host"),
+        ])
+        .start()
+        .await
+        .expect("failed to start foreign container");
+    let current = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    // Fresh containers are spared by default, whoever owns them.
+    let removed = reap_stale_containers().await.expect("failed to reap");
+    assert!(!removed.contains(&stale.id().to_string()));
+
+    let removed = reap_stale_containers_older_than(Duration::ZERO)
+        .await
+        .expect("failed to reap");
+    assert!(removed.contains(&stale.id().to_string()));
+    assert!(!removed.contains(&foreign.id().to_string()));
+    assert!(!removed.contains(&current.container_id().to_string()));
+
+    let docker = Docker::connect_with_local_defaults().expect("failed to connect to docker");
+    assert!(!container_exists(&docker, stale.id()).await);
+    assert!(container_exists(&docker, foreign.id()).await);
+    assert!(container_exists(&docker, current.container_id()).await);
+
+    current
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
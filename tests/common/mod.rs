@@ -2,9 +2,7 @@
 
 use std::path::PathBuf;
 
-use collector_tester::container::{
-    CollectorTestHarness, CollectorTestHarnessBuilder, find_free_port,
-};
+use collector_tester::container::{CollectorTestHarness, CollectorTestHarnessBuilder, Protocol};
 
 #[cfg(target_os = "macos")]
 pub const CONTAINER_HOST: &str = "host.docker.internal";
@@ -20,34 +18,24 @@ pub fn config_path(name: &str) -> PathBuf {
         .join(name)
 }
 
-pub struct TestPorts {
-    pub grpc: u16,
-    pub http: u16,
+/// A stand-in for a collector binary, for the local process backend.
+pub fn fake_binary() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/common/bin/fake-otelcol")
 }
 
-impl TestPorts {
-    pub fn allocate() -> Self {
-        Self {
-            grpc: find_free_port().expect("failed to find free grpc port"),
-            http: find_free_port().expect("failed to find free http port"),
-        }
-    }
-
-    pub fn http_endpoint(&self) -> String {
-        format!("http://127.0.0.1:{}", self.http)
-    }
-
-    pub fn http_traces_endpoint(&self) -> String {
-        format!("http://127.0.0.1:{}/v1/traces", self.http)
-    }
+pub fn harness(config_name: &str) -> CollectorTestHarnessBuilder {
+    CollectorTestHarness::builder(config_path(config_name), "OTLP_EXPORTER_ENDPOINT")
+        .otlp_receiver_ports("COLLECTOR_GRPC_PORT", "COLLECTOR_HTTP_PORT")
+        .health_check_var("COLLECTOR_HEALTH_PORT")
 }
 
-pub fn harness_with_ports(config_name: &str) -> (CollectorTestHarnessBuilder, TestPorts) {
-    let ports = TestPorts::allocate();
-    let builder = CollectorTestHarness::builder(config_path(config_name), "OTLP_EXPORTER_ENDPOINT")
-        .env_var("COLLECTOR_GRPC_PORT", ports.grpc.to_string())
-        .env_var("COLLECTOR_HTTP_PORT", ports.http.to_string())
-        .expose_port(ports.grpc)
-        .expose_port(ports.http);
-    (builder, ports)
+/// A harness around the fake collector binary, for tests that only need its
+/// default sink listening on `protocol`.
+pub async fn local_harness(protocol: Protocol) -> CollectorTestHarness {
+    CollectorTestHarness::builder(config_path("basic.yaml"), "OTLP_EXPORTER_ENDPOINT")
+        .local_binary(fake_binary())
+        .sink_protocol(protocol)
+        .start()
+        .await
+        .expect("failed to start harness")
 }
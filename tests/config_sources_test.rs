@@ -0,0 +1,68 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::CollectorConfig;
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+const SECRET_PATH: &str = "/etc/otelcol-contrib/secrets/tenant";
+
+const OVERLAY: &str = r#"
+processors:
+  attributes/add-test-marker:
+    actions:
+      - key: test.processed
+        value: "overlay"
+        action: insert
+      - key: test.tenant
+        value: ${file:/etc/otelcol-contrib/secrets/tenant}
+        action: insert
+"#;
+
+#[tokio::test]
+async fn test_overlays_are_merged_in_order() {
+    let harness = common::harness("basic.yaml")
+        .add_config(CollectorConfig::inline(OVERLAY))
+        .copy_to(SECRET_PATH, b"acme".to_vec())
+        .env_var(
+            "EXPORTER_TIMEOUT_OVERLAY",
+            "exporters: {otlp/mock: {timeout: 7s}}",
+        )
+        .config_uri("env:EXPORTER_TIMEOUT_OVERLAY")
+        .config_uri("yaml:exporters::otlp/mock::sending_queue::enabled: false")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    let client = TelemetryClient::new(&harness.otlp_http_signal_endpoint(Signal::Traces))
+        .expect("failed to create client");
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder("layered-span").start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+    client.flush().expect("failed to flush");
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+
+    harness
+        .mock_server()
+        .with_collector(|collector| {
+            collector
+                .expect_span_with_name("layered-span")
+                .with_attribute("test.processed", "overlay")
+                .with_attribute("test.tenant", "acme")
+                .assert_exists();
+        })
+        .await;
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use collector_tester::Error;
+use collector_tester::container::{CollectorConfig, CollectorTestHarnessBuilder, find_free_port};
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+const INLINE_CONFIG: &str = r#"
+receivers:
+  otlp:
+    protocols:
+      http:
+        endpoint: 0.0.0.0:{{ http_port }}
+
+extensions:
+  health_check:
+    endpoint: 0.0.0.0:{{ health_port }}
+
+exporters:
+  otlp/mock:
+    endpoint: {{ sink.default.endpoint }}
+    tls:
+      insecure: true
+
+service:
+  extensions: [health_check]
+  telemetry:
+    metrics:
+      level: none
+  pipelines:
+    traces:
+      receivers: [otlp]
+      exporters: [otlp/mock]
+"#;
+
+#[test]
+fn test_render_substitutes_placeholders() {
+    let vars = HashMap::from([
+        ("http_port".to_string(), "4318".to_string()),
+        ("health_port".to_string(), "13133".to_string()),
+        (
+            "sink.default.endpoint".to_string(),
+            "127.0.0.1:9999".to_string(),
+        ),
+    ]);
+
+    let rendered = CollectorConfig::inline(INLINE_CONFIG)
+        .render(&vars)
+        .expect("failed to render");
+
+    assert!(rendered.contains("endpoint: 0.0.0.0:4318"));
+    assert!(rendered.contains("endpoint: 127.0.0.1:9999"));
+    assert!(!rendered.contains("{{"));
+}
+
+#[test]
+fn test_render_rejects_missing_placeholder() {
+    let error = CollectorConfig::inline("endpoint: {{ missing }}")
+        .render(&HashMap::new())
+        .expect_err("missing placeholder should fail");
+
+    assert!(matches!(error, Error::ConfigTemplate { placeholder } if placeholder == "missing"));
+}
+
+#[test]
+fn test_render_yaml_value() {
+    let value: serde_yaml::Value =
+        serde_yaml::from_str("exporters:\n  debug:\n    verbosity: '{{ level }}'\n")
+            .expect("invalid yaml");
+    let vars = HashMap::from([("level".to_string(), "detailed".to_string())]);
+
+    let rendered = CollectorConfig::from(value)
+        .render(&vars)
+        .expect("failed to render");
+
+    assert!(rendered.contains("verbosity: 'detailed'") || rendered.contains("verbosity: detailed"));
+}
+
+#[tokio::test]
+async fn test_inline_config_with_sink_endpoint() {
+    let http_port = find_free_port().expect("failed to find free port");
+    let health_port = find_free_port().expect("failed to find free port");
+
+    let harness = CollectorTestHarnessBuilder::new(
+        CollectorConfig::inline(INLINE_CONFIG),
+        "OTLP_EXPORTER_ENDPOINT",
+    )
+    .template_var("http_port", http_port.to_string())
+    .template_var("health_port", health_port.to_string())
+    .expose_port(http_port)
+    .health_check_port(health_port)
+    .start()
+    .await
+    .expect("failed to start harness");
+
+    let client = TelemetryClient::new(&format!("http://127.0.0.1:{http_port}/v1/traces"))
+        .expect("failed to create client");
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder("inline-config-span").start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+    client.flush().expect("failed to flush");
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::path::PathBuf;
+
+use collector_tester::container::{CollectorConfig, ConfigValidator, Distribution};
+use collector_tester::error::Error;
+
+const CUSTOM_CONFIG_DIR: &str = "/etc/custom-otelcol";
+
+const TOKEN_CONFIG: &str = r#"
+receivers:
+  otlp:
+    protocols:
+      grpc:
+
+exporters:
+  otlp:
+    endpoint: ${file:{{ config_dir }}/secrets/endpoint}
+    tls:
+      insecure: true
+
+service:
+  pipelines:
+    traces:
+      receivers: [otlp]
+      exporters: [otlp]
+"#;
+
+fn test_distribution() -> Distribution {
+    Distribution::build(
+        "test-distribution",
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/common/distribution"),
+    )
+    .config_dir(CUSTOM_CONFIG_DIR)
+}
+
+#[test]
+fn test_published_image_descriptor() {
+    let distribution = Distribution::contrib("0.115.0");
+
+    assert_eq!(
+        distribution.descriptor().unwrap(),
+        "otel/opentelemetry-collector-contrib:0.115.0"
+    );
+}
+
+#[test]
+fn test_built_image_tag_follows_context_contents() {
+    let context = std::env::temp_dir().join(format!("distribution-test-{}", std::process::id()));
+    std::fs::create_dir_all(&context).unwrap();
+    std::fs::write(context.join("Dockerfile"), "FROM scratch\n").unwrap();
+    let distribution = Distribution::build("custom", &context);
+
+    let first = distribution.descriptor().unwrap();
+    assert!(first.starts_with("collector-tester/custom:"));
+    assert_eq!(distribution.descriptor().unwrap(), first);
+
+    std::fs::write(context.join("component.yaml"), "enabled: true\n").unwrap();
+    let second = distribution.descriptor().unwrap();
+    std::fs::remove_dir_all(&context).unwrap();
+
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn test_tag_on_built_distribution_is_rejected() {
+    let result = common::harness("basic.yaml")
+        .distribution(test_distribution())
+        .tag("0.115.0")
+        .start()
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::BuiltDistributionOverride { setting: "tag" })
+    ));
+}
+
+#[tokio::test]
+async fn test_harness_runs_built_distribution() {
+    let harness = common::harness("basic.yaml")
+        .distribution(test_distribution())
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    assert!(harness.is_running().await.unwrap());
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_validator_writes_to_distribution_config_dir() {
+    ConfigValidator::new()
+        .distribution(test_distribution())
+        .copy_to(
+            format!("{CUSTOM_CONFIG_DIR}/secrets/endpoint"),
+            b"127.0.0.1:4317".to_vec(),
+        )
+        .validate(CollectorConfig::inline(TOKEN_CONFIG))
+        .await
+        .expect("config should be valid");
+}
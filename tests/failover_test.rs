@@ -2,42 +2,22 @@ mod common;
 
 use std::time::Duration;
 
-use collector_tester::container::CollectorTestHarness;
-use mock_collector::{MockServer, Protocol};
+use collector_tester::container::CollectorTestHarnessBuilder;
+use mock_collector::Protocol;
 use opentelemetry::trace::{TraceContextExt, Tracer};
 use opentelemetry_configuration::{OtelSdkBuilder, Protocol as OtelProtocol};
 
 #[tokio::test]
 async fn test_failover_to_fallback_on_primary_failure() {
-    let primary_mock = MockServer::builder()
-        .protocol(Protocol::HttpBinary)
+    let harness = CollectorTestHarnessBuilder::from_config(common::config_path("failover.yaml"))
+        .sink("primary", Protocol::HttpBinary, "PRIMARY_ENDPOINT")
+        .sink("fallback", Protocol::Grpc, "FALLBACK_ENDPOINT")
+        .otlp_receiver_ports("COLLECTOR_GRPC_PORT", "COLLECTOR_HTTP_PORT")
         .start()
         .await
-        .expect("failed to start primary mock");
+        .expect("failed to start harness");
 
-    let fallback_mock = MockServer::builder()
-        .protocol(Protocol::Grpc)
-        .start()
-        .await
-        .expect("failed to start fallback mock");
-
-    let ports = common::TestPorts::allocate();
-    let primary_endpoint = format!("{}:{}", common::CONTAINER_HOST, primary_mock.addr().port());
-    let fallback_endpoint = format!("{}:{}", common::CONTAINER_HOST, fallback_mock.addr().port());
-
-    let harness =
-        CollectorTestHarness::builder(common::config_path("failover.yaml"), "PRIMARY_ENDPOINT")
-            .env_var("COLLECTOR_GRPC_PORT", ports.grpc.to_string())
-            .env_var("COLLECTOR_HTTP_PORT", ports.http.to_string())
-            .env_var("PRIMARY_ENDPOINT", &primary_endpoint)
-            .env_var("FALLBACK_ENDPOINT", &fallback_endpoint)
-            .expose_port(ports.grpc)
-            .expose_port(ports.http)
-            .start()
-            .await
-            .expect("failed to start harness");
-
-    let endpoint = ports.http_endpoint();
+    let endpoint = harness.otlp_http_endpoint();
     let _guard = OtelSdkBuilder::new()
         .endpoint(&endpoint)
         .protocol(OtelProtocol::HttpBinary)
@@ -51,12 +31,14 @@ async fn test_failover_to_fallback_on_primary_failure() {
 
     drop(_guard);
 
-    fallback_mock
+    harness
+        .sink("fallback")
         .wait_for_spans(1, Duration::from_secs(15))
         .await
         .expect("timed out waiting for spans at fallback");
 
-    fallback_mock
+    harness
+        .sink("fallback")
         .with_collector(|collector| {
             assert_eq!(collector.span_count(), 1);
             let spans = collector.spans();
@@ -64,7 +46,8 @@ async fn test_failover_to_fallback_on_primary_failure() {
         })
         .await;
 
-    primary_mock
+    harness
+        .sink("primary")
         .with_collector(|collector| {
             assert_eq!(
                 collector.span_count(),
@@ -78,12 +61,4 @@ async fn test_failover_to_fallback_on_primary_failure() {
         .shutdown()
         .await
         .expect("failed to shutdown harness");
-    primary_mock
-        .shutdown()
-        .await
-        .expect("failed to shutdown primary");
-    fallback_mock
-        .shutdown()
-        .await
-        .expect("failed to shutdown fallback");
 }
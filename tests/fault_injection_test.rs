@@ -0,0 +1,122 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::{Fault, Protocol};
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+async fn send_span(endpoint: String, name: &'static str) {
+    tokio::task::spawn_blocking(move || {
+        let client = TelemetryClient::new(&endpoint).expect("failed to create client");
+        let tracer = client.tracer("test");
+        let span = tracer.span_builder(name).start(&tracer);
+        opentelemetry::Context::current_with_span(span).span().end();
+        client.flush().expect("failed to flush");
+    })
+    .await
+    .expect("sender panicked");
+}
+
+#[tokio::test]
+async fn test_exporter_retries_through_unavailable() {
+    let harness = common::harness("retry.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness
+        .mock_server()
+        .faults()
+        .inject_for_requests(Fault::unavailable(), 2);
+
+    send_span(
+        harness.otlp_http_signal_endpoint(Signal::Traces),
+        "retried-span",
+    )
+    .await;
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(15))
+        .await
+        .expect("span never made it through the retries");
+
+    let faults = harness.mock_server().faults();
+    assert_eq!(faults.faulted_requests(), 2);
+    assert_eq!(faults.requests(), 3);
+
+    harness
+        .mock_server()
+        .with_collector(|collector| assert_eq!(collector.span_count(), 1))
+        .await;
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_http_throttling_honours_retry_after() {
+    let harness = common::harness("otlphttp.yaml")
+        .sink_protocol(Protocol::HttpBinary)
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness.mock_server().faults().inject_for(
+        Fault::resource_exhausted().with_retry_after(Duration::from_secs(1)),
+        Duration::from_secs(2),
+    );
+
+    send_span(
+        harness.otlp_http_signal_endpoint(Signal::Traces),
+        "throttled-span",
+    )
+    .await;
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(20))
+        .await
+        .expect("span never made it through the throttling window");
+
+    assert!(harness.mock_server().faults().faulted_requests() >= 1);
+    assert!(!harness.mock_server().faults().is_active());
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_partial_success_is_logged() {
+    let harness = common::harness("retry.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness
+        .mock_server()
+        .faults()
+        .inject(Fault::partial_success(1, "span rejected by test"));
+
+    send_span(
+        harness.otlp_http_signal_endpoint(Signal::Traces),
+        "rejected-span",
+    )
+    .await;
+
+    harness
+        .wait_for_log_line(r"span rejected by test", Duration::from_secs(10))
+        .await
+        .expect("collector did not log the partial success");
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::{InternalMetrics, MetricKind};
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+const EXPOSITION: &str = r#"
+# HELP otelcol_exporter_sent_spans Number of spans successfully sent to destination.
+# TYPE otelcol_exporter_sent_spans counter
+otelcol_exporter_sent_spans_total{exporter="otlp/mock",service_name="otelcol-contrib"} 3
+otelcol_exporter_sent_spans_total{exporter="otlp/other",service_name="otelcol-contrib"} 2
+# HELP otelcol_exporter_queue_size Current size of the retry queue (in batches)
+# TYPE otelcol_exporter_queue_size gauge
+otelcol_exporter_queue_size{data_type="traces",exporter="otlp/mock"} 0
+# TYPE otelcol_processor_batch_batch_send_size histogram
+otelcol_processor_batch_batch_send_size_bucket{processor="batch",le="10"} 4
+otelcol_processor_batch_batch_send_size_bucket{processor="batch",le="+Inf"} 5
+otelcol_processor_batch_batch_send_size_sum{processor="batch"} 27
+otelcol_processor_batch_batch_send_size_count{processor="batch"} 5
+target_info{description="escaped \"quote\", comma\\ and\nnewline"} 1 1700000000000
+"#;
+
+#[test]
+fn test_parse_exposition_format() {
+    let metrics = InternalMetrics::parse(EXPOSITION).expect("failed to parse");
+
+    assert_eq!(metrics.samples().len(), 8);
+    assert_eq!(metrics.sum("otelcol_exporter_sent_spans", &[]), 5.0);
+    assert_eq!(
+        metrics.sum("otelcol_exporter_sent_spans", &[("exporter", "otlp/mock")]),
+        3.0
+    );
+    assert_eq!(metrics.sum("otelcol_exporter_send_failed_spans", &[]), 0.0);
+
+    let queue = metrics.find("otelcol_exporter_queue_size", &[("data_type", "traces")]);
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].kind, MetricKind::Gauge);
+
+    let buckets = metrics.find(
+        "otelcol_processor_batch_batch_send_size_bucket",
+        &[("le", "+Inf")],
+    );
+    assert_eq!(buckets[0].kind, MetricKind::Histogram);
+    assert_eq!(buckets[0].value, 5.0);
+
+    let target = metrics.find("target_info", &[]);
+    assert_eq!(
+        target[0].labels["description"],
+        "escaped \"quote\", comma\\ and\nnewline"
+    );
+}
+
+#[test]
+fn test_parse_rejects_malformed_sample() {
+    assert!(InternalMetrics::parse("otelcol_up{exporter=\"otlp\" 1").is_err());
+    assert!(InternalMetrics::parse("otelcol_up not-a-number").is_err());
+}
+
+#[tokio::test]
+async fn test_exporter_sent_spans_metric() {
+    let harness = common::harness("basic.yaml")
+        .internal_metrics()
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    let client = TelemetryClient::new(&harness.otlp_http_signal_endpoint(Signal::Traces))
+        .expect("failed to create client");
+    let tracer = client.tracer("test");
+    for i in 0..3 {
+        let span = tracer
+            .span_builder(format!("counted-span-{i}"))
+            .start(&tracer);
+        opentelemetry::Context::current_with_span(span).span().end();
+    }
+    client.flush().expect("failed to flush");
+
+    harness
+        .mock_server()
+        .wait_for_spans(3, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+
+    let sent = harness
+        .wait_for_internal_metric(
+            "otelcol_exporter_sent_spans",
+            &[("exporter", "otlp/mock")],
+            |value| value >= 3.0,
+            Duration::from_secs(10),
+        )
+        .await
+        .expect("sent spans metric never reached 3");
+    assert_eq!(sent, 3.0);
+
+    let failed = harness
+        .internal_metric("otelcol_exporter_send_failed_spans", &[])
+        .await
+        .expect("failed to scrape internal metrics");
+    assert_eq!(failed, 0.0);
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::{
+    CollectorTestHarness, CollectorTestHarnessBuilder, GATEWAY_ALIAS, NetworkMode, OTLP_HTTP_PORT,
+};
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+const GRPC_PORT: u16 = 4317;
+const HTTP_PORT: u16 = 4318;
+const HEALTH_PORT: u16 = 13133;
+
+fn isolated_harness() -> CollectorTestHarnessBuilder {
+    CollectorTestHarness::builder(common::config_path("basic.yaml"), "OTLP_EXPORTER_ENDPOINT")
+        .isolated_network()
+        .env_var("COLLECTOR_GRPC_PORT", GRPC_PORT.to_string())
+        .env_var("COLLECTOR_HTTP_PORT", HTTP_PORT.to_string())
+        .env_var("COLLECTOR_HEALTH_PORT", HEALTH_PORT.to_string())
+        .expose_port(GRPC_PORT)
+        .expose_port(HTTP_PORT)
+        .health_check_port(HEALTH_PORT)
+}
+
+async fn send_span(harness: &CollectorTestHarness, name: &'static str) {
+    let port = harness
+        .host_port(HTTP_PORT)
+        .await
+        .expect("http port was not mapped");
+    let endpoint = format!("http://127.0.0.1:{port}/v1/traces");
+
+    tokio::task::spawn_blocking(move || {
+        let client = TelemetryClient::new(&endpoint).expect("failed to create client");
+        let tracer = client.tracer("test");
+        let span = tracer.span_builder(name).start(&tracer);
+        opentelemetry::Context::current_with_span(span).span().end();
+        client.flush().expect("failed to flush");
+        client.shutdown().expect("failed to shutdown client");
+    })
+    .await
+    .expect("sender panicked");
+}
+
+#[tokio::test]
+async fn test_parallel_harnesses_share_container_ports() {
+    let (first, second) = tokio::join!(isolated_harness().start(), isolated_harness().start());
+    let first = first.expect("failed to start first harness");
+    let second = second.expect("failed to start second harness");
+
+    assert_eq!(first.network_mode(), NetworkMode::Isolated);
+    assert_eq!(first.mock_host(), GATEWAY_ALIAS);
+    assert_ne!(
+        first.host_port(HTTP_PORT).await.unwrap(),
+        second.host_port(HTTP_PORT).await.unwrap()
+    );
+
+    send_span(&first, "first-span").await;
+    send_span(&second, "second-span").await;
+
+    for (harness, name) in [(&first, "first-span"), (&second, "second-span")] {
+        harness
+            .mock_server()
+            .wait_for_spans(1, Duration::from_secs(10))
+            .await
+            .expect("timed out waiting for spans");
+        harness
+            .mock_server()
+            .with_collector(|collector| {
+                assert_eq!(collector.span_count(), 1);
+                collector.expect_span_with_name(name).assert_exists();
+            })
+            .await;
+    }
+
+    first.shutdown().await.expect("failed to shutdown harness");
+    second.shutdown().await.expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_named_ports_use_fixed_container_ports() {
+    let harness = common::harness("basic.yaml")
+        .isolated_network()
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    assert_eq!(
+        harness.host_port(HTTP_PORT).await.unwrap(),
+        harness.port(OTLP_HTTP_PORT)
+    );
+    send_span(&harness, "named-port-span").await;
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+fn send_span(endpoint: &str, name: &'static str) -> TelemetryClient {
+    let client = TelemetryClient::new(endpoint).expect("failed to create client");
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder(name).start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+    client.flush().expect("failed to flush");
+    client
+}
+
+#[tokio::test]
+async fn test_graceful_stop_exits_cleanly() {
+    let harness = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness
+        .stop(Duration::from_secs(10))
+        .await
+        .expect("failed to stop collector");
+
+    assert!(!harness.is_running().await.expect("failed to inspect"));
+    assert_eq!(
+        harness.exit_code().await.expect("failed to inspect"),
+        Some(0)
+    );
+    harness
+        .wait_for_log_line("Shutdown complete", Duration::from_secs(5))
+        .await
+        .expect("collector did not log a clean shutdown");
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_kill_then_restart_with_same_config() {
+    let mut harness = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness.kill().await.expect("failed to kill collector");
+    let exit_code = harness
+        .wait_for_exit(Duration::from_secs(10))
+        .await
+        .expect("collector did not exit");
+    assert_eq!(exit_code, Some(137));
+
+    harness
+        .restart()
+        .await
+        .expect("failed to restart collector");
+    assert!(harness.is_running().await.expect("failed to inspect"));
+
+    let client = send_span(
+        &harness.otlp_http_signal_endpoint(Signal::Traces),
+        "after-restart",
+    );
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("sink did not receive spans after restart");
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_pause_holds_telemetry_until_unpause() {
+    let harness = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness.pause().await.expect("failed to pause collector");
+
+    let endpoint = harness.otlp_http_signal_endpoint(Signal::Traces);
+    let sender = tokio::task::spawn_blocking(move || send_span(&endpoint, "paused-span"));
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    harness
+        .mock_server()
+        .with_collector(|collector| assert_eq!(collector.span_count(), 0))
+        .await;
+
+    harness
+        .unpause()
+        .await
+        .expect("failed to unpause collector");
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("sink did not receive spans after unpause");
+
+    let client = sender.await.expect("sender panicked");
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use collector_tester::input::{
+    LOAD_EXPONENTIAL_HISTOGRAM, LoadConfig, LoadGenerator, Operation, Protocol, Resource,
+    TelemetryClient, TraceShape,
+};
+use collector_tester::monitor::LoadTestHarness;
+use mock_collector::MockServer;
+use opentelemetry_proto::tonic::metrics::v1::metric::Data;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generates_all_signals_concurrently() {
+    let server = MockServer::builder()
+        .protocol(Protocol::Grpc)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let client = TelemetryClient::builder()
+        .endpoint(format!("http://{}", server.addr()))
+        .protocol(Protocol::Grpc)
+        .metric_interval(Duration::from_millis(100))
+        .exponential_histogram(LOAD_EXPONENTIAL_HISTOGRAM)
+        .build()
+        .expect("failed to create client");
+
+    let config = LoadConfig {
+        spans_per_second: 20,
+        metrics_per_second: 40,
+        logs_per_second: 20,
+        duration: Duration::from_secs(1),
+        ..Default::default()
+    };
+    let stats = LoadGenerator::new(&client, config)
+        .run()
+        .await
+        .expect("load generation failed");
+
+    // Every signal ran for the whole second rather than one after another.
+    assert!(stats.duration < Duration::from_secs(2));
+    assert!((15..=25).contains(&stats.spans_sent), "{stats:?}");
+    assert!((35..=45).contains(&stats.metrics_sent), "{stats:?}");
+    assert!((15..=25).contains(&stats.logs_sent), "{stats:?}");
+    assert!(stats.logs_per_second() > 0.0);
+
+    server
+        .wait_for_spans(stats.spans_sent, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for spans");
+    server
+        .wait_for_logs(stats.logs_sent, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for logs");
+    server
+        .wait_for_metrics(4, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for metrics");
+
+    let kinds: HashMap<String, &'static str> = server
+        .with_collector(|collector| {
+            collector
+                .metrics()
+                .iter()
+                .map(|metric| {
+                    let kind = match metric.metric().data {
+                        Some(Data::Sum(_)) => "sum",
+                        Some(Data::Gauge(_)) => "gauge",
+                        Some(Data::Histogram(_)) => "histogram",
+                        Some(Data::ExponentialHistogram(_)) => "exponential",
+                        _ => "other",
+                    };
+                    (metric.metric().name.clone(), kind)
+                })
+                .collect()
+        })
+        .await;
+    assert_eq!(kinds["load.requests"], "sum");
+    assert_eq!(kinds["load.queue.size"], "gauge");
+    assert_eq!(kinds["load.latency"], "histogram");
+    assert_eq!(kinds[LOAD_EXPONENTIAL_HISTOGRAM], "exponential");
+
+    client.shutdown().expect("failed to shutdown client");
+    server.shutdown().await.expect("failed to stop mock server");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generates_shaped_traces() {
+    let server = MockServer::builder()
+        .protocol(Protocol::Grpc)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let shape =
+        TraceShape::new(Operation::new("frontend", "GET /").call(Operation::new("api", "list")));
+    let client = TelemetryClient::builder()
+        .endpoint(format!("http://{}", server.addr()))
+        .protocol(Protocol::Grpc)
+        .service_name("frontend")
+        .add_resource(Resource::builder().with_service_name("api").build())
+        .build()
+        .expect("failed to create client");
+
+    let config = LoadConfig {
+        spans_per_second: 30,
+        metrics_per_second: 0,
+        logs_per_second: 0,
+        duration: Duration::from_millis(500),
+        trace_shape: Some(shape),
+        ..Default::default()
+    };
+    let stats = LoadGenerator::new(&client, config)
+        .run()
+        .await
+        .expect("load generation failed");
+
+    assert_eq!(stats.spans_sent % 3, 0);
+    assert_eq!((stats.metrics_sent, stats.logs_sent), (0, 0));
+    server
+        .wait_for_spans(stats.spans_sent, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for spans");
+    server
+        .with_collector(|collector| {
+            collector
+                .expect_span_with_name("list")
+                .with_resource_attribute("service.name", "api")
+                .assert_count(stats.spans_sent / 3);
+        })
+        .await;
+
+    client.shutdown().expect("failed to shutdown client");
+    server.shutdown().await.expect("failed to stop mock server");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_load_test_sends_every_signal_over_http() {
+    let harness = common::local_harness(Protocol::HttpBinary).await;
+    let endpoint = format!(
+        "http://127.0.0.1:{}/v1/traces",
+        harness.mock_server().addr().port()
+    );
+    let mut load_test = LoadTestHarness::new(harness, endpoint)
+        .await
+        .expect("failed to create load test");
+
+    let config = LoadConfig {
+        spans_per_second: 10,
+        metrics_per_second: 10,
+        logs_per_second: 10,
+        duration: Duration::from_millis(500),
+        ..Default::default()
+    };
+    load_test
+        .run_load_test(config.clone(), Duration::from_millis(100))
+        .await
+        .expect("load test failed");
+
+    let sink = load_test.harness().mock_server();
+    let timeout = Duration::from_secs(5);
+    sink.wait_for_spans(1, timeout)
+        .await
+        .expect("timed out waiting for spans");
+    sink.wait_for_metrics(1, timeout)
+        .await
+        .expect("timed out waiting for metrics");
+    sink.wait_for_logs(1, timeout)
+        .await
+        .expect("timed out waiting for logs");
+
+    // The monitor follows the collector to its new pid.
+    load_test
+        .harness_mut()
+        .restart()
+        .await
+        .expect("failed to restart");
+    let result = load_test
+        .run_load_test(config, Duration::from_millis(100))
+        .await
+        .expect("load test after restart failed");
+    assert!(result.memory_analysis.sample_count > 0);
+
+    load_test.shutdown().await.expect("failed to shutdown");
+}
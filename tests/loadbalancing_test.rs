@@ -2,42 +2,23 @@ mod common;
 
 use std::time::Duration;
 
-use collector_tester::container::CollectorTestHarness;
-use mock_collector::{MockServer, Protocol};
+use collector_tester::container::CollectorTestHarnessBuilder;
+use mock_collector::Protocol;
 use opentelemetry::trace::{TraceContextExt, Tracer};
 use opentelemetry_configuration::{OtelSdkBuilder, Protocol as OtelProtocol};
 
 #[tokio::test]
 async fn test_loadbalancing_distributes_spans() {
-    let backend_1 = MockServer::builder()
-        .protocol(Protocol::Grpc)
-        .start()
-        .await
-        .expect("failed to start backend 1");
-
-    let backend_2 = MockServer::builder()
-        .protocol(Protocol::Grpc)
-        .start()
-        .await
-        .expect("failed to start backend 2");
-
-    let ports = common::TestPorts::allocate();
-    let backend_1_addr = format!("{}:{}", common::CONTAINER_HOST, backend_1.addr().port());
-    let backend_2_addr = format!("{}:{}", common::CONTAINER_HOST, backend_2.addr().port());
-
     let harness =
-        CollectorTestHarness::builder(common::config_path("loadbalancing.yaml"), "BACKEND_1")
-            .env_var("COLLECTOR_GRPC_PORT", ports.grpc.to_string())
-            .env_var("COLLECTOR_HTTP_PORT", ports.http.to_string())
-            .env_var("BACKEND_1", &backend_1_addr)
-            .env_var("BACKEND_2", &backend_2_addr)
-            .expose_port(ports.grpc)
-            .expose_port(ports.http)
+        CollectorTestHarnessBuilder::from_config(common::config_path("loadbalancing.yaml"))
+            .sink("backend_1", Protocol::Grpc, "BACKEND_1")
+            .sink("backend_2", Protocol::Grpc, "BACKEND_2")
+            .otlp_receiver_ports("COLLECTOR_GRPC_PORT", "COLLECTOR_HTTP_PORT")
             .start()
             .await
             .expect("failed to start harness");
 
-    let endpoint = ports.http_endpoint();
+    let endpoint = harness.otlp_http_endpoint();
     let _guard = OtelSdkBuilder::new()
         .endpoint(&endpoint)
         .protocol(OtelProtocol::HttpBinary)
@@ -60,13 +41,15 @@ async fn test_loadbalancing_distributes_spans() {
     let mut backend_1_count = 0;
     let mut backend_2_count = 0;
 
-    backend_1
+    harness
+        .sink("backend_1")
         .with_collector(|collector| {
             backend_1_count = collector.span_count();
         })
         .await;
 
-    backend_2
+    harness
+        .sink("backend_2")
         .with_collector(|collector| {
             backend_2_count = collector.span_count();
         })
@@ -90,12 +73,4 @@ async fn test_loadbalancing_distributes_spans() {
         .shutdown()
         .await
         .expect("failed to shutdown harness");
-    backend_1
-        .shutdown()
-        .await
-        .expect("failed to shutdown backend 1");
-    backend_2
-        .shutdown()
-        .await
-        .expect("failed to shutdown backend 2");
 }
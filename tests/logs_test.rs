@@ -0,0 +1,69 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+#[tokio::test]
+async fn test_healthy_collector_logs_no_errors() {
+    let harness = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness
+        .wait_for_log_line(r"Everything is ready", Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for ready log line");
+
+    let client = TelemetryClient::new(&harness.otlp_http_signal_endpoint(Signal::Traces))
+        .expect("failed to create client");
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder("logged-span").start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+    client.flush().expect("failed to flush");
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+
+    harness.assert_no_errors_logged();
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_exporter_failure_is_visible_in_logs() {
+    let harness = common::harness("basic.yaml")
+        .env_var("OTLP_EXPORTER_ENDPOINT", "127.0.0.1:1")
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    let client = TelemetryClient::new(&harness.otlp_http_signal_endpoint(Signal::Traces))
+        .expect("failed to create client");
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder("undeliverable-span").start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+    client.flush().expect("failed to flush");
+
+    let line = harness
+        .wait_for_log_line(r"Exporting failed", Duration::from_secs(15))
+        .await
+        .expect("timed out waiting for export failure");
+    assert!(line.contains("otlp/mock"), "unexpected log line: {line}");
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::Router;
+use axum::http::header;
+use axum::routing::post;
+use collector_tester::container::Fault;
+use collector_tester::error::{Error, Result, Signal};
+use collector_tester::input::{OtlpResponse, OtlpSender, OtlpStatus, PartialSuccess, Protocol};
+use mock_collector::MockServer;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::common::v1::{AnyValue, KeyValue, any_value};
+use opentelemetry_proto::tonic::resource::v1::Resource;
+use opentelemetry_proto::tonic::trace::v1::{ResourceSpans, ScopeSpans, Span};
+use tokio::net::TcpListener;
+
+/// A span from 2020, which the SDK could not produce.
+fn historical_request() -> ExportTraceServiceRequest {
+    let start = 1_577_836_800_000_000_000;
+    ExportTraceServiceRequest {
+        resource_spans: vec![ResourceSpans {
+            resource: Some(Resource {
+                attributes: vec![KeyValue {
+                    key: "service.name".to_string(),
+                    value: Some(AnyValue {
+                        value: Some(any_value::Value::StringValue("raw-sender".to_string())),
+                    }),
+                }],
+                ..Default::default()
+            }),
+            scope_spans: vec![ScopeSpans {
+                spans: vec![Span {
+                    trace_id: vec![0xab; 16],
+                    span_id: vec![0xcd; 8],
+                    name: "raw-span".to_string(),
+                    start_time_unix_nano: start,
+                    end_time_unix_nano: start + 1_000_000,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+    }
+}
+
+async fn send_raw_span(protocol: Protocol) {
+    let server = MockServer::builder()
+        .protocol(protocol)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let response = OtlpSender::new(protocol, format!("http://{}", server.addr()))
+        .header("X-Tenant", "acme")
+        .send_traces(&historical_request())
+        .await
+        .expect("failed to send traces");
+    assert!(response.is_success(), "export failed: {response:?}");
+    assert_eq!(response.rejected(), 0);
+
+    server
+        .wait_for_spans(1, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for spans");
+    server
+        .with_collector(|collector| {
+            collector
+                .expect_span_with_name("raw-span")
+                .with_resource_attribute("service.name", "raw-sender")
+                .assert_count(1);
+        })
+        .await;
+
+    server.shutdown().await.expect("failed to stop mock server");
+}
+
+#[tokio::test]
+async fn test_raw_traces_over_grpc() {
+    send_raw_span(Protocol::Grpc).await;
+}
+
+#[tokio::test]
+async fn test_raw_traces_over_http_protobuf() {
+    send_raw_span(Protocol::HttpBinary).await;
+}
+
+#[tokio::test]
+async fn test_raw_traces_over_http_json() {
+    send_raw_span(Protocol::HttpJson).await;
+}
+
+#[tokio::test]
+async fn test_malformed_payload_is_rejected() {
+    let server = MockServer::builder()
+        .protocol(Protocol::Grpc)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let response = OtlpSender::new(Protocol::Grpc, format!("http://{}", server.addr()))
+        .send_raw(Signal::Traces, vec![0xff, 0xff, 0xff])
+        .await
+        .expect("failed to send payload");
+    assert!(!response.is_success());
+    assert!(matches!(response.status, OtlpStatus::Grpc(code) if code != tonic::Code::Ok));
+
+    server.shutdown().await.expect("failed to stop mock server");
+}
+
+/// Sends a span to a sink that rejects part of every export.
+async fn send_with_partial_success(protocol: Protocol) -> Result<OtlpResponse> {
+    let harness = common::local_harness(protocol).await;
+    let sink = harness.mock_server();
+    sink.faults()
+        .inject(Fault::partial_success(2, "span too large"));
+
+    let response = OtlpSender::new(protocol, format!("http://127.0.0.1:{}", sink.addr().port()))
+        .send_traces(&historical_request())
+        .await;
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+    response
+}
+
+/// Sends a span over OTLP/JSON to an endpoint that answers with `body`,
+/// which the sink, encoding its responses canonically, could not produce.
+async fn send_with_json_response(body: &'static str) -> Result<OtlpResponse> {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind listener");
+    let addr = listener.local_addr().expect("listener has no address");
+    let app = Router::new().route(
+        "/v1/traces",
+        post(move || async move { ([(header::CONTENT_TYPE, "application/json")], body) }),
+    );
+    let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let response = OtlpSender::new(Protocol::HttpJson, format!("http://{addr}"))
+        .send_traces(&historical_request())
+        .await;
+    server.abort();
+    response
+}
+
+fn rejected(count: i64, message: &str) -> Option<PartialSuccess> {
+    Some(PartialSuccess {
+        rejected: count,
+        error_message: message.to_string(),
+    })
+}
+
+#[tokio::test]
+async fn test_partial_success_over_grpc() {
+    let response = send_with_partial_success(Protocol::Grpc)
+        .await
+        .expect("failed to send traces");
+
+    assert!(response.is_success(), "export failed: {response:?}");
+    assert_eq!(response.partial_success, rejected(2, "span too large"));
+}
+
+#[tokio::test]
+async fn test_partial_success_over_http_protobuf() {
+    let response = send_with_partial_success(Protocol::HttpBinary)
+        .await
+        .expect("failed to send traces");
+
+    assert!(response.is_success(), "export failed: {response:?}");
+    assert_eq!(response.partial_success, rejected(2, "span too large"));
+}
+
+#[tokio::test]
+async fn test_partial_success_over_http_json_with_string_count() {
+    let response = send_with_json_response(
+        r#"{"partialSuccess":{"rejectedSpans":"3","errorMessage":"quota exceeded"}}"#,
+    )
+    .await
+    .expect("failed to send traces");
+
+    assert_eq!(response.partial_success, rejected(3, "quota exceeded"));
+    assert_eq!(response.rejected(), 3);
+}
+
+#[tokio::test]
+async fn test_partial_success_over_http_json_omits_zero_values() {
+    let warning =
+        send_with_json_response(r#"{"partialSuccess":{"errorMessage":"attributes truncated"}}"#)
+            .await
+            .expect("failed to send traces");
+    assert_eq!(warning.partial_success, rejected(0, "attributes truncated"));
+
+    let empty = send_with_json_response(r#"{"partialSuccess":{}}"#)
+        .await
+        .expect("failed to send traces");
+    assert_eq!(empty.partial_success, None);
+}
+
+#[tokio::test]
+async fn test_malformed_json_count_is_an_error() {
+    let result = send_with_json_response(r#"{"partialSuccess":{"rejectedSpans":"several"}}"#).await;
+
+    assert!(matches!(
+        result,
+        Err(Error::OtlpSend {
+            signal: Signal::Traces,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_unreachable_http_endpoint_is_a_send_error() {
+    // Bound and dropped, so nothing listens on the port.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind listener")
+        .local_addr()
+        .expect("listener has no address");
+
+    let result = OtlpSender::new(Protocol::HttpBinary, format!("http://{addr}"))
+        .send_traces(&historical_request())
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::OtlpSend {
+            signal: Signal::Traces,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::DurabilityReport;
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+const STORAGE_DIR: &str = "/var/lib/otelcol/file_storage";
+
+#[test]
+fn test_report_counts_duplicates_and_losses() {
+    let report = DurabilityReport {
+        sent: 10,
+        received: 11,
+        unique: 9,
+    };
+
+    assert_eq!(report.duplicates(), 2);
+    assert_eq!(report.lost(), 1);
+}
+
+#[tokio::test]
+async fn test_queued_spans_survive_a_crash() {
+    let mut harness = common::harness("persistent-queue.yaml")
+        .storage_dir(STORAGE_DIR)
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    assert!(harness.storage().and_then(|s| s.host_dir()).is_some());
+
+    let endpoint = harness.otlp_http_signal_endpoint(Signal::Traces);
+    let report = harness
+        .check_durability(
+            "default",
+            Signal::Traces,
+            20,
+            || async move {
+                tokio::task::spawn_blocking(move || {
+                    let client = TelemetryClient::new(&endpoint)?;
+                    let tracer = client.tracer("test");
+                    for i in 0..20 {
+                        let span = tracer.span_builder(format!("queued-{i}")).start(&tracer);
+                        opentelemetry::Context::current_with_span(span).span().end();
+                    }
+                    client.flush()?;
+                    client.shutdown()
+                })
+                .await
+                .expect("sender panicked")
+            },
+            Duration::from_secs(30),
+        )
+        .await
+        .expect("durability check failed");
+
+    report.assert_exactly_once();
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::CollectorPool;
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+async fn send_span(endpoint: String, name: &'static str) {
+    tokio::task::spawn_blocking(move || {
+        let client = TelemetryClient::new(&endpoint).expect("failed to create client");
+        let tracer = client.tracer("test");
+        let span = tracer.span_builder(name).start(&tracer);
+        opentelemetry::Context::current_with_span(span).span().end();
+        client.flush().expect("failed to flush");
+        client.shutdown().expect("failed to shutdown client");
+    })
+    .await
+    .expect("sender panicked");
+}
+
+#[tokio::test]
+async fn test_pool_reuses_collector_with_reset_sinks() {
+    let pool = CollectorPool::new();
+
+    let first = pool
+        .acquire(common::harness("basic.yaml"))
+        .await
+        .expect("failed to start pooled harness");
+    let container_id = first.container_id().to_string();
+
+    send_span(
+        first.otlp_http_signal_endpoint(Signal::Traces),
+        "first-test",
+    )
+    .await;
+    first
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+    drop(first);
+    assert_eq!(pool.idle_count(), 1);
+
+    let second = pool
+        .acquire(common::harness("basic.yaml"))
+        .await
+        .expect("failed to lease pooled harness");
+    assert_eq!(second.container_id(), container_id);
+    assert_eq!(pool.idle_count(), 0);
+    second
+        .mock_server()
+        .with_collector(|collector| assert_eq!(collector.span_count(), 0))
+        .await;
+
+    send_span(
+        second.otlp_http_signal_endpoint(Signal::Traces),
+        "second-test",
+    )
+    .await;
+    second
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+    second
+        .mock_server()
+        .with_collector(|collector| {
+            assert_eq!(collector.span_count(), 1);
+            collector
+                .expect_span_with_name("second-test")
+                .assert_exists();
+        })
+        .await;
+
+    let other = pool
+        .acquire(common::harness("resource-attributes.yaml"))
+        .await
+        .expect("failed to start pooled harness");
+    assert_ne!(other.container_id(), container_id);
+
+    // Each setup keeps its own warm collector.
+    drop(second);
+    drop(other);
+    assert_eq!(pool.idle_count(), 2);
+
+    let third = pool
+        .acquire(common::harness("basic.yaml"))
+        .await
+        .expect("failed to lease pooled harness");
+    assert_eq!(third.container_id(), container_id);
+    assert_eq!(pool.idle_count(), 1);
+    drop(third);
+
+    pool.clear().await.expect("failed to clear pool");
+    assert_eq!(pool.idle_count(), 0);
+}
+
+#[tokio::test]
+async fn test_pool_evicts_longest_idle_collector_beyond_cap() {
+    let pool = CollectorPool::with_max_idle(1);
+
+    let basic = pool
+        .acquire(common::harness("basic.yaml"))
+        .await
+        .expect("failed to start pooled harness");
+    let other = pool
+        .acquire(common::harness("resource-attributes.yaml"))
+        .await
+        .expect("failed to start pooled harness");
+    let other_id = other.container_id().to_string();
+
+    drop(basic);
+    drop(other);
+    assert_eq!(pool.idle_count(), 1);
+
+    let reused = pool
+        .acquire(common::harness("resource-attributes.yaml"))
+        .await
+        .expect("failed to lease pooled harness");
+    assert_eq!(reused.container_id(), other_id);
+    drop(reused);
+
+    pool.clear().await.expect("failed to clear pool");
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::{OTLP_GRPC_PORT, OTLP_HTTP_PORT};
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+#[tokio::test]
+async fn test_receiver_ports_are_allocated_per_harness() {
+    let (first, second) = tokio::join!(
+        common::harness("basic.yaml").start(),
+        common::harness("basic.yaml").start()
+    );
+    let first = first.expect("failed to start first harness");
+    let second = second.expect("failed to start second harness");
+
+    assert_ne!(first.port(OTLP_HTTP_PORT), second.port(OTLP_HTTP_PORT));
+    assert_ne!(first.port(OTLP_GRPC_PORT), first.port(OTLP_HTTP_PORT));
+    assert_eq!(
+        first.otlp_grpc_endpoint(),
+        format!("http://127.0.0.1:{}", first.port(OTLP_GRPC_PORT))
+    );
+
+    for (harness, name) in [(&first, "first-span"), (&second, "second-span")] {
+        let endpoint = harness.otlp_http_signal_endpoint(Signal::Traces);
+        tokio::task::spawn_blocking(move || {
+            let client = TelemetryClient::new(&endpoint).expect("failed to create client");
+            let tracer = client.tracer("test");
+            let span = tracer.span_builder(name).start(&tracer);
+            opentelemetry::Context::current_with_span(span).span().end();
+            client.flush().expect("failed to flush");
+            client.shutdown().expect("failed to shutdown client");
+        })
+        .await
+        .expect("sender panicked");
+
+        harness
+            .mock_server()
+            .wait_for_spans(1, Duration::from_secs(10))
+            .await
+            .expect("timed out waiting for spans");
+        harness
+            .mock_server()
+            .with_collector(|collector| {
+                assert_eq!(collector.span_count(), 1);
+                collector.expect_span_with_name(name).assert_exists();
+            })
+            .await;
+    }
+
+    first.shutdown().await.expect("failed to shutdown harness");
+    second.shutdown().await.expect("failed to shutdown harness");
+}
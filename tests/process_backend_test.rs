@@ -0,0 +1,72 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::{CollectorTestHarness, NetworkMode};
+use collector_tester::error::Error;
+use collector_tester::monitor::ProcessMonitor;
+
+#[tokio::test]
+async fn test_local_process_lifecycle() {
+    let mut harness =
+        CollectorTestHarness::builder(common::config_path("basic.yaml"), "OTLP_EXPORTER_ENDPOINT")
+            .local_binary(common::fake_binary())
+            .validate_config()
+            .start()
+            .await
+            .expect("failed to start harness");
+
+    let pid = harness.backend().pid().expect("local process has a pid");
+    assert!(harness.is_running().await.unwrap());
+    assert!(!harness.logs().contains("/etc/otelcol-contrib"));
+    assert!(harness.logs().contains("config.yaml"));
+    assert!(harness.logs().contains("endpoint: 127.0.0.1:"));
+
+    harness
+        .stop(Duration::from_secs(5))
+        .await
+        .expect("failed to stop");
+    assert_eq!(harness.exit_code().await.unwrap(), Some(0));
+    assert!(matches!(
+        harness.signal("SIGHUP").await,
+        Err(Error::ProcessNotRunning)
+    ));
+    assert!(matches!(
+        harness.signal("SIGBOGUS").await,
+        Err(Error::UnknownSignal { .. })
+    ));
+    harness
+        .wait_for_log_line("shutting down", Duration::from_secs(5))
+        .await
+        .expect("collector did not log its shutdown");
+
+    harness.restart().await.expect("failed to restart");
+    assert!(harness.is_running().await.unwrap());
+    assert_ne!(harness.backend().pid(), Some(pid));
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_isolated_network_unsupported_by_local_process() {
+    let result = common::harness("basic.yaml")
+        .local_binary(common::fake_binary())
+        .network_mode(NetworkMode::Isolated)
+        .start()
+        .await;
+
+    assert!(matches!(result, Err(Error::UnsupportedByBackend { .. })));
+}
+
+#[tokio::test]
+async fn test_process_monitor_samples_proc_status() {
+    let mut monitor = ProcessMonitor::new(std::process::id());
+
+    let snapshot = monitor.sample().await.expect("failed to sample");
+    assert!(snapshot.usage_bytes > 0);
+    assert!(snapshot.max_usage_bytes >= snapshot.usage_bytes);
+    assert_eq!(monitor.analyse().sample_count, 1);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use collector_tester::container::{DEFAULT_SINK, FaultProxy};
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+fn send_span(endpoint: &str, name: &'static str) -> TelemetryClient {
+    let client = TelemetryClient::new(endpoint).expect("failed to create client");
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder(name).start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+    client.flush().expect("failed to flush");
+    client
+}
+
+async fn echo_server() -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind echo server");
+    let addr = listener.local_addr().expect("no local addr");
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut read, mut write) = stream.split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    addr
+}
+
+async fn round_trip(stream: &mut TcpStream, payload: &[u8]) -> std::io::Result<Vec<u8>> {
+    stream.write_all(payload).await?;
+    let mut buffer = vec![0; payload.len()];
+    stream.read_exact(&mut buffer).await?;
+    Ok(buffer)
+}
+
+#[tokio::test]
+async fn test_proxy_blackhole_holds_and_restore_releases_traffic() {
+    let proxy = FaultProxy::start(echo_server().await)
+        .await
+        .expect("failed to start proxy");
+    let mut stream = TcpStream::connect(("127.0.0.1", proxy.addr().port()))
+        .await
+        .expect("failed to connect");
+
+    assert_eq!(round_trip(&mut stream, b"ping").await.unwrap(), b"ping");
+
+    proxy.blackhole();
+    let held = tokio::time::timeout(Duration::from_millis(300), round_trip(&mut stream, b"held"));
+    assert!(held.await.is_err(), "traffic crossed a blackholed proxy");
+
+    proxy.restore();
+    let mut buffer = [0; 4];
+    tokio::time::timeout(Duration::from_secs(2), stream.read_exact(&mut buffer))
+        .await
+        .expect("traffic was not released after restore")
+        .expect("read failed");
+    assert_eq!(&buffer, b"held");
+}
+
+#[tokio::test]
+async fn test_proxy_resets_and_delays_connections() {
+    let proxy = FaultProxy::start(echo_server().await)
+        .await
+        .expect("failed to start proxy");
+    let mut stream = TcpStream::connect(("127.0.0.1", proxy.addr().port()))
+        .await
+        .expect("failed to connect");
+    assert_eq!(round_trip(&mut stream, b"ping").await.unwrap(), b"ping");
+
+    proxy.reset_connections();
+    let mut buffer = [0; 1];
+    let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buffer))
+        .await
+        .expect("connection was not closed");
+    assert!(matches!(read, Ok(0) | Err(_)));
+
+    proxy.set_latency(Duration::from_millis(200));
+    let mut stream = TcpStream::connect(("127.0.0.1", proxy.addr().port()))
+        .await
+        .expect("failed to reconnect");
+    let start = Instant::now();
+    assert_eq!(round_trip(&mut stream, b"slow").await.unwrap(), b"slow");
+    assert!(start.elapsed() >= Duration::from_millis(400));
+}
+
+#[tokio::test]
+async fn test_proxy_faults_reset_the_client() {
+    let proxy = FaultProxy::start(echo_server().await)
+        .await
+        .expect("failed to start proxy");
+    let mut stream = TcpStream::connect(("127.0.0.1", proxy.addr().port()))
+        .await
+        .expect("failed to connect");
+    assert_eq!(round_trip(&mut stream, b"ping").await.unwrap(), b"ping");
+
+    proxy.reset_connections();
+    let mut buffer = [0; 1];
+    let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buffer))
+        .await
+        .expect("connection was not reset");
+    assert_eq!(
+        read.expect_err("connection closed without a reset").kind(),
+        std::io::ErrorKind::ConnectionReset
+    );
+
+    proxy.reject_connections();
+    let mut stream = TcpStream::connect(("127.0.0.1", proxy.addr().port()))
+        .await
+        .expect("failed to connect");
+    let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buffer))
+        .await
+        .expect("connection was not rejected");
+    assert_eq!(
+        read.expect_err("connection closed without a reset").kind(),
+        std::io::ErrorKind::ConnectionReset
+    );
+}
+
+#[tokio::test]
+async fn test_proxy_half_close_keeps_response() {
+    let proxy = FaultProxy::start(echo_server().await)
+        .await
+        .expect("failed to start proxy");
+    proxy.set_latency(Duration::from_millis(100));
+    let mut stream = TcpStream::connect(("127.0.0.1", proxy.addr().port()))
+        .await
+        .expect("failed to connect");
+
+    stream.write_all(b"request").await.expect("write failed");
+    stream.shutdown().await.expect("shutdown failed");
+
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut response))
+        .await
+        .expect("response did not arrive")
+        .expect("read failed");
+    assert_eq!(response, b"request");
+}
+
+#[tokio::test]
+async fn test_exporter_recovers_after_blackhole() {
+    let harness = common::harness("retry.yaml")
+        .proxy(DEFAULT_SINK)
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    harness.proxy(DEFAULT_SINK).blackhole();
+
+    let client = send_span(
+        &harness.otlp_http_signal_endpoint(Signal::Traces),
+        "blackholed-span",
+    );
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    harness
+        .mock_server()
+        .with_collector(|collector| assert_eq!(collector.span_count(), 0))
+        .await;
+
+    harness.proxy(DEFAULT_SINK).restore();
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(20))
+        .await
+        .expect("span never arrived after the proxy was restored");
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
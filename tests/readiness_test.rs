@@ -0,0 +1,67 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use collector_tester::Error;
+
+#[tokio::test]
+async fn test_ready_via_health_check() {
+    let builder = common::harness("basic.yaml");
+
+    let start = Instant::now();
+    let harness = builder
+        .startup_timeout(Duration::from_secs(20))
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    assert!(
+        start.elapsed() < Duration::from_secs(20),
+        "collector should be ready before the startup timeout"
+    );
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_unhealthy_health_check_blocks_startup() {
+    // The collector logs "Everything is ready", but the health check path
+    // never answers successfully, so startup must wait on the health check.
+    let result = common::harness("basic.yaml")
+        .health_check_path("/never-healthy")
+        .startup_timeout(Duration::from_secs(10))
+        .start()
+        .await;
+
+    match result {
+        Err(Error::StartupTimeout { logs, .. }) => {
+            assert!(logs.contains("Everything is ready"), "{logs}");
+        }
+        Err(other) => panic!("expected StartupTimeout, got: {other}"),
+        Ok(_) => panic!("startup did not wait on the health check"),
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_config_returns_logs() {
+    let builder = common::harness("invalid-pipeline.yaml");
+
+    let result = builder
+        .startup_timeout(Duration::from_secs(20))
+        .start()
+        .await;
+
+    match result {
+        Err(Error::CollectorExited { logs, .. }) => {
+            assert!(
+                logs.contains("otlp/missing"),
+                "expected logs to mention the missing exporter, got:\n{logs}"
+            );
+        }
+        Err(other) => panic!("expected CollectorExited, got: {other}"),
+        Ok(_) => panic!("expected startup to fail"),
+    }
+}
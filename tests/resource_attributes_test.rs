@@ -7,10 +7,12 @@ use opentelemetry_configuration::{OtelSdkBuilder, Protocol};
 
 #[tokio::test]
 async fn test_resource_attribute_added_to_all_signals() {
-    let (builder, ports) = common::harness_with_ports("resource-attributes.yaml");
-    let harness = builder.start().await.expect("failed to start harness");
+    let harness = common::harness("resource-attributes.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
 
-    let endpoint = ports.http_endpoint();
+    let endpoint = harness.otlp_http_endpoint();
     let _guard = OtelSdkBuilder::new()
         .endpoint(&endpoint)
         .protocol(Protocol::HttpBinary)
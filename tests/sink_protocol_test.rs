@@ -0,0 +1,61 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::Protocol;
+use collector_tester::error::Signal;
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+async fn assert_span_reaches_sink(protocol: Protocol, encoding: &str) {
+    let harness = common::harness("otlphttp.yaml")
+        .sink_protocol(protocol)
+        .env_var("OTLP_EXPORTER_ENCODING", encoding)
+        .start()
+        .await
+        .expect("failed to start harness");
+
+    assert!(harness.mock_server().endpoint().starts_with("http://"));
+
+    let endpoint = harness.otlp_http_signal_endpoint(Signal::Traces);
+    tokio::task::spawn_blocking(move || {
+        let client = TelemetryClient::new(&endpoint).expect("failed to create client");
+        let tracer = client.tracer("test");
+        let span = tracer.span_builder("otlphttp-span").start(&tracer);
+        opentelemetry::Context::current_with_span(span).span().end();
+        client.flush().expect("failed to flush");
+        client.shutdown().expect("failed to shutdown client");
+    })
+    .await
+    .expect("sender panicked");
+
+    harness
+        .mock_server()
+        .wait_for_spans(1, Duration::from_secs(10))
+        .await
+        .expect("timed out waiting for spans");
+
+    harness
+        .mock_server()
+        .with_collector(|collector| {
+            collector
+                .expect_span_with_name("otlphttp-span")
+                .assert_exists();
+        })
+        .await;
+
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test]
+async fn test_http_binary_sink() {
+    assert_span_reaches_sink(Protocol::HttpBinary, "proto").await;
+}
+
+#[tokio::test]
+async fn test_http_json_sink() {
+    assert_span_reaches_sink(Protocol::HttpJson, "json").await;
+}
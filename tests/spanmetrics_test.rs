@@ -8,14 +8,16 @@ use opentelemetry_configuration::{OtelSdkBuilder, Protocol};
 
 #[tokio::test]
 async fn test_spanmetrics_generates_metrics_and_reports_memory() {
-    let (builder, ports) = common::harness_with_ports("spanmetrics.yaml");
-    let harness = builder.start().await.expect("failed to start harness");
+    let harness = common::harness("spanmetrics.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
 
     let mut monitor = ContainerMonitor::new(harness.container_id())
         .await
         .expect("failed to create monitor");
 
-    let endpoint = ports.http_endpoint();
+    let endpoint = harness.otlp_http_endpoint();
     let _guard = OtelSdkBuilder::new()
         .endpoint(&endpoint)
         .protocol(Protocol::HttpBinary)
@@ -0,0 +1,286 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use collector_tester::Error;
+use collector_tester::container::DEFAULT_SINK;
+use collector_tester::error::Signal;
+use collector_tester::input::{
+    Compression, Processor, Protocol, Resource, TelemetryClient, Temporality,
+};
+use mock_collector::MockServer;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+use opentelemetry_proto::tonic::metrics::v1::AggregationTemporality;
+use opentelemetry_proto::tonic::metrics::v1::metric::Data;
+use opentelemetry_proto::tonic::metrics::v1::number_data_point::Value;
+use tokio::net::TcpListener;
+
+async fn send_span_and_counter(protocol: Protocol, compression: Compression, processor: Processor) {
+    let harness = common::local_harness(protocol).await;
+    let sink = harness.sink(DEFAULT_SINK);
+
+    let client = TelemetryClient::builder()
+        .endpoint(format!("http://127.0.0.1:{}", sink.addr().port()))
+        .protocol(protocol)
+        .compression(compression)
+        .header("X-Tenant", "acme")
+        .traces(|traces| traces.processor(processor))
+        .metric_interval(Duration::from_millis(100))
+        .build()
+        .expect("failed to create client");
+
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder("builder-span").start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+    client
+        .meter("test")
+        .u64_counter("builder.requests")
+        .build()
+        .add(1, &[]);
+    client.flush().expect("failed to flush");
+
+    sink.wait_for_spans(1, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for spans");
+    sink.wait_for_metrics(1, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for metrics");
+
+    let (encoding_header, encoding) = match (protocol, compression) {
+        (Protocol::Grpc, Compression::Gzip) => ("grpc-encoding", "gzip"),
+        (Protocol::Grpc, _) => ("grpc-encoding", "zstd"),
+        (_, Compression::Gzip) => ("content-encoding", "gzip"),
+        (_, _) => ("content-encoding", "zstd"),
+    };
+    for signal in [Signal::Traces, Signal::Metrics] {
+        let headers = sink
+            .last_headers(signal)
+            .unwrap_or_else(|| panic!("no {signal} export recorded"));
+        assert_eq!(headers["x-tenant"], "acme", "{signal}");
+        assert_eq!(headers[encoding_header], encoding, "{signal}");
+    }
+
+    client.shutdown().expect("failed to shutdown client");
+    harness
+        .shutdown()
+        .await
+        .expect("failed to shutdown harness");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_grpc_with_gzip() {
+    send_span_and_counter(Protocol::Grpc, Compression::Gzip, Processor::Simple).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_http_protobuf_with_zstd() {
+    send_span_and_counter(Protocol::HttpBinary, Compression::Zstd, Processor::Batch).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_http_json_with_gzip() {
+    send_span_and_counter(Protocol::HttpJson, Compression::Gzip, Processor::Batch).await;
+}
+
+#[tokio::test]
+async fn test_missing_endpoint_is_rejected() {
+    let result = TelemetryClient::builder()
+        .logs(|logs| logs.endpoint("http://127.0.0.1:4317"))
+        .build();
+
+    assert!(matches!(result, Err(Error::ExporterBuild { .. })));
+}
+
+#[tokio::test]
+async fn test_simple_processor_over_http_is_rejected() {
+    let result = TelemetryClient::builder()
+        .endpoint("http://127.0.0.1:4318")
+        .protocol(Protocol::HttpBinary)
+        .logs(|logs| logs.processor(Processor::Simple))
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(Error::ExporterBuild {
+            signal: Signal::Logs,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_invalid_grpc_metadata_is_rejected() {
+    let result = TelemetryClient::builder()
+        .endpoint("http://127.0.0.1:4317")
+        .header("bad header", "value")
+        .build();
+
+    assert!(matches!(result, Err(Error::ExporterBuild { .. })));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_spans_carry_each_configured_resource() {
+    let server = MockServer::builder()
+        .protocol(Protocol::Grpc)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let client = TelemetryClient::builder()
+        .endpoint(format!("http://{}", server.addr()))
+        .service_name("checkout")
+        .resource_attribute("deployment.environment", "staging")
+        .schema_url("https://opentelemetry.io/schemas/1.26.0")
+        .add_resource(Resource::builder().with_service_name("payments").build())
+        .add_resource(Resource::builder().with_service_name("inventory").build())
+        .build()
+        .expect("failed to create client");
+
+    assert_eq!(client.resources().len(), 3);
+    assert_eq!(
+        client.resources()[0].resource().schema_url(),
+        Some("https://opentelemetry.io/schemas/1.26.0")
+    );
+    for resource in client.resources() {
+        let tracer = resource.tracer("test");
+        let span = tracer.span_builder("resource-span").start(&tracer);
+        opentelemetry::Context::current_with_span(span).span().end();
+    }
+    client.flush().expect("failed to flush");
+
+    server
+        .wait_for_spans(3, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for spans");
+    server
+        .with_collector(|collector| {
+            collector
+                .expect_span_with_name("resource-span")
+                .with_resource_attribute("service.name", "checkout")
+                .with_resource_attribute("deployment.environment", "staging")
+                .assert_count(1);
+            for service in ["payments", "inventory"] {
+                collector
+                    .expect_span_with_name("resource-span")
+                    .with_resource_attribute("service.name", service)
+                    .assert_count(1);
+            }
+        })
+        .await;
+
+    client.shutdown().expect("failed to shutdown client");
+    server.shutdown().await.expect("failed to stop mock server");
+}
+
+/// The temporality and values of `requests` as exported after adding 1,
+/// flushing, then adding 2.
+async fn exported_counter(temporality: Temporality) -> (i32, Vec<i64>) {
+    let server = MockServer::builder()
+        .protocol(Protocol::Grpc)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let client = TelemetryClient::builder()
+        .endpoint(format!("http://{}", server.addr()))
+        .temporality(temporality)
+        .metric_interval(Duration::from_secs(60))
+        .build()
+        .expect("failed to create client");
+    let counter = client.meter("test").u64_counter("requests").build();
+    for value in [1, 2] {
+        counter.add(value, &[]);
+        client.flush_metrics().expect("failed to flush metrics");
+    }
+
+    server
+        .wait_for_metrics(2, Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for metrics");
+    let exported = server
+        .with_collector(|collector| {
+            collector
+                .metrics()
+                .iter()
+                .map(|metric| match &metric.metric().data {
+                    Some(Data::Sum(sum)) => match sum.data_points[0].value {
+                        Some(Value::AsInt(value)) => (sum.aggregation_temporality, value),
+                        ref other => panic!("expected an integer sum, got {other:?}"),
+                    },
+                    other => panic!("expected a sum, got {other:?}"),
+                })
+                .collect::<Vec<_>>()
+        })
+        .await;
+
+    client.shutdown().expect("failed to shutdown client");
+    server.shutdown().await.expect("failed to stop mock server");
+
+    let temporality = exported[0].0;
+    assert!(exported.iter().all(|(other, _)| *other == temporality));
+    (
+        temporality,
+        exported.into_iter().map(|(_, value)| value).collect(),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cumulative_temporality_exports_running_totals() {
+    let (temporality, values) = exported_counter(Temporality::Cumulative).await;
+
+    assert_eq!(temporality, AggregationTemporality::Cumulative as i32);
+    assert_eq!(values, [1, 3]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_delta_temporality_exports_changes_since_last_export() {
+    let (temporality, values) = exported_counter(Temporality::Delta).await;
+
+    assert_eq!(temporality, AggregationTemporality::Delta as i32);
+    assert_eq!(values, [1, 2]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_export_to_unresponsive_endpoint_times_out() {
+    // Accepts connections but never answers on them.
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind listener");
+    let addr = listener.local_addr().expect("listener has no address");
+    let accept = tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((connection, _)) = listener.accept().await {
+            connections.push(connection);
+        }
+    });
+
+    let timeout = Duration::from_millis(500);
+    let client = TelemetryClient::builder()
+        .endpoint(format!("http://{addr}"))
+        .timeout(timeout)
+        .build()
+        .expect("failed to create client");
+    let tracer = client.tracer("test");
+    let span = tracer.span_builder("never-answered").start(&tracer);
+    opentelemetry::Context::current_with_span(span).span().end();
+
+    let start = Instant::now();
+    let result = client.flush_traces();
+    let elapsed = start.elapsed();
+
+    assert!(
+        matches!(
+            result,
+            Err(Error::Flush {
+                signal: Signal::Traces,
+                ..
+            })
+        ),
+        "expected a flush error, got {result:?}"
+    );
+    assert!(elapsed >= timeout, "failed after {elapsed:?}");
+    assert!(elapsed < Duration::from_secs(5), "failed after {elapsed:?}");
+
+    let _ = client.shutdown();
+    accept.abort();
+}
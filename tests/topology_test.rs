@@ -0,0 +1,152 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::{
+    CollectorTestHarnessBuilder, DEFAULT_SINK, Protocol, TopologyHarness,
+};
+use collector_tester::error::{Error, Signal};
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+fn agent() -> CollectorTestHarnessBuilder {
+    agent_with("agent.yaml")
+}
+
+fn agent_with(config: &str) -> CollectorTestHarnessBuilder {
+    CollectorTestHarnessBuilder::from_config(common::config_path(config))
+        .otlp_receiver_ports("COLLECTOR_GRPC_PORT", "COLLECTOR_HTTP_PORT")
+        .health_check_var("COLLECTOR_HEALTH_PORT")
+}
+
+async fn send_span(topology: &TopologyHarness, node: &str, name: &'static str) {
+    let endpoint = topology
+        .node(node)
+        .otlp_http_signal_endpoint(Signal::Traces);
+    tokio::task::spawn_blocking(move || {
+        let client = TelemetryClient::new(&endpoint).expect("failed to create client");
+        let tracer = client.tracer("test");
+        let span = tracer.span_builder(name).start(&tracer);
+        opentelemetry::Context::current_with_span(span).span().end();
+        client.flush().expect("failed to flush");
+        client.shutdown().expect("failed to shutdown client");
+    })
+    .await
+    .expect("sender panicked");
+}
+
+#[tokio::test]
+async fn test_forwarding_cycle_is_rejected() {
+    let result = TopologyHarness::builder()
+        .node("a", agent())
+        .node("b", agent())
+        .forward("a", "b", Protocol::Grpc, "GATEWAY_ENDPOINT")
+        .forward("b", "a", Protocol::Grpc, "GATEWAY_ENDPOINT")
+        .start()
+        .await;
+
+    match result {
+        Err(Error::TopologyCycle { nodes }) => assert_eq!(nodes, ["a", "b"]),
+        Err(other) => panic!("expected a cycle error, got {other}"),
+        Ok(_) => panic!("expected a cycle error"),
+    }
+}
+
+#[tokio::test]
+async fn test_forward_to_unknown_node_is_rejected() {
+    let result = TopologyHarness::builder()
+        .node("agent", agent())
+        .forward("agent", "gateway", Protocol::Grpc, "GATEWAY_ENDPOINT")
+        .start()
+        .await;
+
+    match result {
+        Err(Error::UnknownNode { node }) => assert_eq!(node, "gateway"),
+        Err(other) => panic!("expected an unknown node error, got {other}"),
+        Ok(_) => panic!("expected an unknown node error"),
+    }
+}
+
+#[tokio::test]
+async fn test_agents_forward_through_gateway() {
+    let topology = TopologyHarness::builder()
+        .node("agent-1", agent())
+        .node("agent-2", agent_with("agent-http.yaml"))
+        .node("gateway", common::harness("basic.yaml"))
+        .forward("agent-1", "gateway", Protocol::Grpc, "GATEWAY_ENDPOINT")
+        .forward(
+            "agent-2",
+            "gateway",
+            Protocol::HttpBinary,
+            "GATEWAY_ENDPOINT",
+        )
+        .start()
+        .await
+        .expect("failed to start topology");
+
+    let order: Vec<_> = topology.nodes().map(|(name, _)| name).collect();
+    assert_eq!(order, ["gateway", "agent-1", "agent-2"]);
+
+    for (node, name) in [("agent-1", "via-agent-1"), ("agent-2", "via-agent-2")] {
+        send_span(&topology, node, name).await;
+    }
+
+    let sink = topology.sink("gateway", DEFAULT_SINK);
+    sink.wait_for_spans(2, Duration::from_secs(15))
+        .await
+        .expect("timed out waiting for spans at the gateway sink");
+
+    sink.with_collector(|collector| {
+        assert_eq!(collector.span_count(), 2);
+        for name in ["via-agent-1", "via-agent-2"] {
+            collector
+                .expect_span_with_name(name)
+                .with_resource_attributes([("collector.tier", "agent")])
+                .with_attribute("test.processed", "true")
+                .assert_exists();
+        }
+    })
+    .await;
+
+    topology
+        .shutdown()
+        .await
+        .expect("failed to shutdown topology");
+}
+
+#[tokio::test]
+async fn test_stop_flushes_queued_agent_data_into_gateway() {
+    let topology = TopologyHarness::builder()
+        .node("agent", agent_with("agent-batched.yaml"))
+        .node("gateway", common::harness("basic.yaml"))
+        .forward("agent", "gateway", Protocol::Grpc, "GATEWAY_ENDPOINT")
+        .start()
+        .await
+        .expect("failed to start topology");
+
+    send_span(&topology, "agent", "queued-in-agent").await;
+
+    // The agent batches for an hour, so nothing reaches the gateway until
+    // the agent is stopped and flushes.
+    let sink = topology.sink("gateway", DEFAULT_SINK);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    sink.with_collector(|collector| assert_eq!(collector.span_count(), 0))
+        .await;
+
+    topology
+        .stop(Duration::from_secs(10))
+        .await
+        .expect("failed to stop topology");
+    sink.with_collector(|collector| {
+        collector
+            .expect_span_with_name("queued-in-agent")
+            .with_resource_attributes([("collector.tier", "agent")])
+            .assert_exists();
+    })
+    .await;
+
+    topology
+        .shutdown()
+        .await
+        .expect("failed to shutdown topology");
+}
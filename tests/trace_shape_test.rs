@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+use collector_tester::Error;
+use collector_tester::input::{
+    Latency, Operation, OtlpSender, Processor, Protocol, Resource, SyntheticTrace, TelemetryClient,
+    TraceGenerator, TraceShape,
+};
+use mock_collector::{MockServer, ServerHandle};
+use opentelemetry::trace::SpanKind;
+
+fn checkout_shape() -> TraceShape {
+    TraceShape::new(
+        Operation::new("frontend", "GET /checkout").call(
+            Operation::new("checkout", "PlaceOrder")
+                .call(Operation::new("checkout", "validate-cart"))
+                .call(Operation::new("payments", "Charge").error_rate(1.0))
+                .call(Operation::new("shipping", "Quote")),
+        ),
+    )
+    .latency(Latency::Uniform {
+        min: Duration::from_millis(1),
+        max: Duration::from_millis(20),
+    })
+}
+
+/// (span ID, parent span ID, service) of every span the mock received.
+async fn received_spans(server: &ServerHandle) -> HashSet<(Vec<u8>, Vec<u8>, String)> {
+    server
+        .with_collector(|collector| {
+            collector
+                .spans()
+                .iter()
+                .map(|span| {
+                    let service = span
+                        .resource_attrs()
+                        .iter()
+                        .find(|attribute| attribute.key == "service.name")
+                        .and_then(|attribute| attribute.value.clone()?.value)
+                        .map(|value| format!("{value:?}"))
+                        .unwrap_or_default();
+                    (
+                        span.span().span_id.clone(),
+                        span.span().parent_span_id.clone(),
+                        service,
+                    )
+                })
+                .collect()
+        })
+        .await
+}
+
+fn expected_spans(trace: &SyntheticTrace) -> HashSet<(Vec<u8>, Vec<u8>, String)> {
+    trace
+        .spans
+        .iter()
+        .map(|span| {
+            let parent = span
+                .parent
+                .map(|parent| trace.spans[parent].span_id.to_bytes().to_vec())
+                .unwrap_or_default();
+            (
+                span.span_id.to_bytes().to_vec(),
+                parent,
+                format!("StringValue({:?})", span.service),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_shape_produces_client_server_pairs() {
+    let shape = checkout_shape();
+    assert_eq!(
+        shape.services(),
+        ["frontend", "checkout", "payments", "shipping"]
+    );
+    assert_eq!(shape.span_count(), 8);
+
+    let trace = TraceGenerator::new(shape)
+        .expect("invalid shape")
+        .seed(7)
+        .next_trace();
+    let kinds: Vec<_> = trace
+        .spans
+        .iter()
+        .map(|span| (span.service.as_str(), span.kind.clone()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("frontend", SpanKind::Server),
+            ("frontend", SpanKind::Client),
+            ("checkout", SpanKind::Server),
+            ("checkout", SpanKind::Internal),
+            ("checkout", SpanKind::Client),
+            ("payments", SpanKind::Server),
+            ("checkout", SpanKind::Client),
+            ("shipping", SpanKind::Server),
+        ]
+    );
+
+    for span in &trace.spans {
+        assert!(span.end >= span.start);
+        if let Some(parent) = span.parent.map(|parent| &trace.spans[parent]) {
+            assert!(span.start >= parent.start && span.end <= parent.end);
+        }
+    }
+    assert!(trace.spans[4].error && trace.spans[5].error);
+    assert!(!trace.spans[0].error);
+}
+
+#[test]
+fn test_invalid_error_rates_are_rejected() {
+    let nan = TraceShape::new(Operation::new("frontend", "GET /")).error_rate(f64::NAN);
+    assert!(matches!(
+        TraceGenerator::new(nan),
+        Err(Error::InvalidErrorRate { rate }) if rate.is_nan()
+    ));
+
+    let too_high = TraceShape::new(
+        Operation::new("frontend", "GET /").call(Operation::new("api", "list").error_rate(1.5)),
+    );
+    assert!(matches!(
+        TraceGenerator::new(too_high),
+        Err(Error::InvalidErrorRate { rate }) if rate == 1.5
+    ));
+}
+
+#[test]
+fn test_seeded_generators_repeat() {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    let first = TraceGenerator::new(checkout_shape())
+        .expect("invalid shape")
+        .seed(42)
+        .next_trace_at(start);
+    let second = TraceGenerator::new(checkout_shape())
+        .expect("invalid shape")
+        .seed(42)
+        .next_trace_at(start);
+
+    assert_eq!(first.to_request(), second.to_request());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_trace_emitted_through_client_resources() {
+    let server = MockServer::builder()
+        .protocol(Protocol::Grpc)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let shape = checkout_shape();
+    let mut services = shape.services().into_iter();
+    let mut builder = TelemetryClient::builder()
+        .endpoint(format!("http://{}", server.addr()))
+        .protocol(Protocol::Grpc)
+        .processor(Processor::Simple)
+        .service_name(services.next().unwrap());
+    for service in services {
+        builder = builder.add_resource(Resource::builder().with_service_name(service).build());
+    }
+    let client = builder.build().expect("failed to create client");
+
+    let trace = TraceGenerator::new(shape)
+        .expect("invalid shape")
+        .next_trace();
+    trace.emit(&client).expect("failed to emit trace");
+    client.flush().expect("failed to flush");
+
+    server
+        .wait_for_spans(trace.spans.len(), Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for spans");
+    assert_eq!(received_spans(&server).await, expected_spans(&trace));
+    server
+        .with_collector(|collector| {
+            collector
+                .expect_span_with_name("Charge")
+                .with_resource_attribute("service.name", "payments")
+                .assert_count(1);
+        })
+        .await;
+
+    client.shutdown().expect("failed to shutdown client");
+    server.shutdown().await.expect("failed to stop mock server");
+}
+
+#[tokio::test]
+async fn test_historical_trace_through_raw_sender() {
+    let server = MockServer::builder()
+        .protocol(Protocol::HttpBinary)
+        .start()
+        .await
+        .expect("failed to start mock server");
+
+    let trace = TraceGenerator::new(checkout_shape())
+        .expect("invalid shape")
+        .seed(1)
+        .next_trace_at(SystemTime::UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+    let response = trace
+        .send(&OtlpSender::new(
+            Protocol::HttpBinary,
+            format!("http://{}", server.addr()),
+        ))
+        .await
+        .expect("failed to send trace");
+    assert!(response.is_success(), "export failed: {response:?}");
+
+    server
+        .wait_for_spans(trace.spans.len(), Duration::from_secs(5))
+        .await
+        .expect("timed out waiting for spans");
+    assert_eq!(received_spans(&server).await, expected_spans(&trace));
+
+    server.shutdown().await.expect("failed to stop mock server");
+}
+
+#[tokio::test]
+async fn test_emit_requires_resource_per_service() {
+    let client = TelemetryClient::builder()
+        .endpoint("http://127.0.0.1:4317")
+        .service_name("frontend")
+        .build()
+        .expect("failed to create client");
+
+    let result = TraceGenerator::new(checkout_shape())
+        .expect("invalid shape")
+        .next_trace()
+        .emit(&client);
+
+    assert!(matches!(result, Err(Error::UnknownService { service }) if service == "checkout"));
+    client.shutdown().expect("failed to shutdown client");
+}
@@ -2,15 +2,18 @@ mod common;
 
 use std::time::Duration;
 
+use collector_tester::error::Signal;
 use collector_tester::input::TelemetryClient;
 use opentelemetry::trace::{TraceContextExt, Tracer};
 
 #[tokio::test]
 async fn test_basic_trace_processing() {
-    let (builder, ports) = common::harness_with_ports("basic.yaml");
-    let harness = builder.start().await.expect("failed to start harness");
+    let harness = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
 
-    let endpoint = ports.http_traces_endpoint();
+    let endpoint = harness.otlp_http_signal_endpoint(Signal::Traces);
     let client = TelemetryClient::new(&endpoint).expect("failed to create client");
 
     let tracer = client.tracer("test");
@@ -48,10 +51,12 @@ async fn test_basic_trace_processing() {
 
 #[tokio::test]
 async fn test_attribute_processor() {
-    let (builder, ports) = common::harness_with_ports("basic.yaml");
-    let harness = builder.start().await.expect("failed to start harness");
+    let harness = common::harness("basic.yaml")
+        .start()
+        .await
+        .expect("failed to start harness");
 
-    let endpoint = ports.http_traces_endpoint();
+    let endpoint = harness.otlp_http_signal_endpoint(Signal::Traces);
     let client = TelemetryClient::new(&endpoint).expect("failed to create client");
 
     let tracer = client.tracer("test");
@@ -0,0 +1,75 @@
+mod common;
+
+use collector_tester::Error;
+use collector_tester::container::{ConfigIssue, ConfigValidator};
+
+const MISSING_EXPORTER_OUTPUT: &str = "\
+Error: invalid configuration: service::pipelines::traces: references exporter \"otlp/missing\" which is not configured
+2025/01/01 00:00:00 collector server run finished with error: invalid configuration: service::pipelines::traces: references exporter \"otlp/missing\" which is not configured
+";
+
+const UNKNOWN_TYPE_OUTPUT: &str = "\
+Error: failed to get config: cannot unmarshal the configuration: decoding failed due to the following error(s):
+
+'receivers' unknown type: \"bogus\" for id: \"bogus\" (valid values: [otlp jaeger])
+'exporters::otlp/mock' has invalid keys: endpiont
+2025/01/01 00:00:00 collector server run finished with error: failed to get config
+";
+
+#[test]
+fn test_parse_missing_pipeline_reference() {
+    let issues = ConfigIssue::parse(MISSING_EXPORTER_OUTPUT);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].pipeline.as_deref(), Some("traces"));
+    assert_eq!(
+        issues[0].component.as_deref(),
+        Some("exporters::otlp/missing")
+    );
+}
+
+#[test]
+fn test_parse_decoding_errors() {
+    let issues = ConfigIssue::parse(UNKNOWN_TYPE_OUTPUT);
+
+    let components: Vec<_> = issues
+        .iter()
+        .filter_map(|issue| issue.component.as_deref())
+        .collect();
+    assert_eq!(components, ["receivers::bogus", "exporters::otlp/mock"]);
+    assert!(issues.iter().all(|issue| issue.pipeline.is_none()));
+}
+
+#[tokio::test]
+async fn test_validator_reports_missing_exporter() {
+    let result = ConfigValidator::new()
+        .env_var("COLLECTOR_GRPC_PORT", "4317")
+        .env_var("COLLECTOR_HTTP_PORT", "4318")
+        .env_var("OTLP_EXPORTER_ENDPOINT", "127.0.0.1:4317")
+        .validate(common::config_path("invalid-pipeline.yaml"))
+        .await;
+
+    match result {
+        Err(Error::InvalidConfig { issues, .. }) => {
+            assert!(
+                issues
+                    .iter()
+                    .any(|issue| issue.component.as_deref() == Some("exporters::otlp/missing")),
+                "expected an issue for otlp/missing, got: {issues:?}"
+            );
+        }
+        other => panic!("expected InvalidConfig, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_harness_validates_before_starting() {
+    let builder = common::harness("invalid-pipeline.yaml");
+
+    let result = builder.validate_config().start().await;
+
+    assert!(
+        matches!(result, Err(Error::InvalidConfig { .. })),
+        "expected InvalidConfig"
+    );
+}
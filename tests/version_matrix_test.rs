@@ -0,0 +1,95 @@
+mod common;
+
+use std::time::Duration;
+
+use collector_tester::container::{CollectorVersion, VersionMatrix, VersionOutcome};
+use collector_tester::error::{Error, Signal};
+use collector_tester::input::TelemetryClient;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+
+#[tokio::test]
+async fn test_matrix_reports_each_version() {
+    let report = VersionMatrix::new()
+        .tags(["0.110.0", "0.115.0"])
+        .version("example/otelcol", "1.0.0")
+        .run(|version| async move {
+            match version.tag.as_str() {
+                "0.110.0" => Ok(()),
+                "0.115.0" => Err(Error::ConfigTemplate {
+                    placeholder: "missing".to_string(),
+                }),
+                _ => panic!("unsupported image {}", version.image),
+            }
+        })
+        .await;
+
+    assert!(!report.all_passed());
+    assert_eq!(report.results().len(), 3);
+    assert_eq!(
+        report
+            .result(&CollectorVersion::contrib("0.110.0"))
+            .unwrap()
+            .outcome,
+        VersionOutcome::Passed
+    );
+    assert!(matches!(
+        &report.result(&CollectorVersion::contrib("0.115.0")).unwrap().outcome,
+        VersionOutcome::Failed(message) if message.contains("missing")
+    ));
+    assert_eq!(
+        report
+            .result(&CollectorVersion::new("example/otelcol", "1.0.0"))
+            .unwrap()
+            .outcome,
+        VersionOutcome::Panicked("unsupported image example/otelcol".to_string())
+    );
+    assert_eq!(report.failures().count(), 2);
+
+    let text = report.to_string();
+    assert!(text.contains("otel/opentelemetry-collector-contrib:0.110.0"));
+    assert!(text.contains("PANICKED: unsupported image example/otelcol"));
+}
+
+#[tokio::test]
+async fn test_basic_pipeline_across_versions() {
+    let report = VersionMatrix::new()
+        .tags(["0.115.0", "latest"])
+        .run(|version| async move {
+            let harness = common::harness("basic.yaml")
+                .version(&version)
+                .start()
+                .await?;
+
+            let endpoint = harness.otlp_http_signal_endpoint(Signal::Traces);
+            tokio::task::spawn_blocking(move || {
+                let client = TelemetryClient::new(&endpoint)?;
+                let tracer = client.tracer("test");
+                let span = tracer.span_builder("matrix-span").start(&tracer);
+                opentelemetry::Context::current_with_span(span).span().end();
+                client.flush()?;
+                client.shutdown()
+            })
+            .await
+            .expect("sender panicked")?;
+
+            harness
+                .mock_server()
+                .wait_for_spans(1, Duration::from_secs(10))
+                .await?;
+            harness
+                .mock_server()
+                .with_collector(|collector| {
+                    collector
+                        .expect_span_with_name("matrix-span")
+                        .with_attribute("test.processed", "true")
+                        .assert_exists();
+                })
+                .await;
+
+            harness.shutdown().await
+        })
+        .await;
+
+    println!("{report}");
+    report.assert_all_passed();
+}